@@ -1,33 +1,179 @@
 use std::sync::Arc;
 
-use anyhow::Context;
+use anyhow::{anyhow, ensure, Context};
 use log4rs::Handle;
-use maa_cfg::{Config, Parameters};
-use maa_core::tauri_logger::log_config;
-use tauri::{async_runtime::spawn_blocking, AppHandle, State};
+use maa_cfg::{backup::Backup, Config, Parameters, QueueKind};
+use maa_core::{
+    registry::{self, RunInfo},
+    state::{self, RunState},
+    tauri_logger::log_config,
+};
+use serde::{Deserialize, Serialize};
+use tauri::{
+    async_runtime::{spawn, spawn_blocking},
+    AppHandle, Manager, State,
+};
+use tokio::sync::Semaphore;
 
-use crate::{log_error_context, CommandResult};
+use crate::{
+    log_error_context,
+    profile::{apply_language, is_language_setting, ConfigState},
+    CommandResult,
+};
 
 #[tauri::command]
-pub async fn run_daily(configs: State<'_, Arc<Config>>) -> CommandResult<()> {
+pub async fn run_daily(configs: State<'_, ConfigState>) -> CommandResult<()> {
+    let configs = configs.current();
     let tasks = configs.available_daily_tasks();
     let adb_cfg = configs
         .adb_config()
         .context("get adb config")
         .map_err(|e| log_error_context("run daily", e))?;
 
-    spawn_blocking(move || maa_core::run_core_tauri(tasks, adb_cfg))
+    let id = configs.name().to_string();
+    spawn_blocking(move || maa_core::run_core_tauri(&id, tasks, adb_cfg))
         .await
         .unwrap()
         .map_err(|e| log_error_context("run daily", e))
 }
 
+/// run enabled tasks of a queue, `kind` is one of `daily`, `extra`, `customs`
+/// or the name of a custom queue in `customs.json`.
 #[tauri::command]
-pub async fn stop_core() -> CommandResult<()> {
-    spawn_blocking(move || maa_callback::callback::STOP_CHAN.tx.send(()))
+pub async fn run_queue(kind: String, configs: State<'_, ConfigState>) -> CommandResult<()> {
+    let kind: QueueKind = kind.parse().unwrap();
+    let context = format!("run queue {kind}");
+    let configs = configs.current();
+    let tasks = configs
+        .task_queue(&kind)
+        .and_then(|tasks| {
+            ensure!(!tasks.is_empty(), "no enabled task in {kind}");
+            Ok(tasks)
+        })
+        .map_err(|e| log_error_context(&context, e))?;
+    let adb_cfg = configs
+        .adb_config()
+        .context("get adb config")
+        .map_err(|e| log_error_context(&context, e))?;
+
+    let id = configs.name().to_string();
+    spawn_blocking(move || maa_core::run_core_tauri(&id, tasks, adb_cfg))
         .await
         .unwrap()
-        .map_err(|e| log_error_context("send stop sign", e))
+        .map_err(|e| log_error_context(&context, e))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct BatchInstance {
+    /// profile to run, also used as instance id
+    pub profile: String,
+    /// queue kind, see `run_queue`, defaults to `daily`
+    #[serde(default)]
+    pub queue: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct BatchResult {
+    pub profile: String,
+    /// `None` if the run succeeded
+    pub error: Option<String>,
+}
+
+/// run queues of several profiles in parallel, each with its own adb settings.
+/// at most `concurrency` instances run at the same time.
+#[tauri::command]
+pub async fn run_batch(
+    instances: Vec<BatchInstance>,
+    concurrency: usize,
+    handle: AppHandle,
+) -> CommandResult<Vec<BatchResult>> {
+    let semaphore = Arc::new(Semaphore::new(concurrency.max(1)));
+    let handles: Vec<_> = instances
+        .into_iter()
+        .map(|instance| {
+            let semaphore = semaphore.clone();
+            let handle = handle.clone();
+            spawn(async move {
+                let _permit = semaphore.acquire_owned().await.expect("never closed");
+                let error = run_instance(&handle, &instance).await.err().map(|e| {
+                    let message = format!("{e:#}");
+                    log_error_context(&format!("run {}", instance.profile), e);
+                    message
+                });
+                BatchResult {
+                    profile: instance.profile,
+                    error,
+                }
+            })
+        })
+        .collect();
+
+    let mut results = vec![];
+    for handle in handles {
+        results.push(
+            handle
+                .await
+                .map_err(|e| log_error_context("join batch run", e))?,
+        );
+    }
+    Ok(results)
+}
+
+pub(crate) async fn run_instance(
+    handle: &AppHandle,
+    instance: &BatchInstance,
+) -> anyhow::Result<()> {
+    let config = handle
+        .state::<ConfigState>()
+        .profile(instance.profile.clone())
+        .await?;
+    let kind: QueueKind = instance
+        .queue
+        .as_deref()
+        .unwrap_or("daily")
+        .parse()
+        .unwrap();
+    let tasks = config.task_queue(&kind)?;
+    ensure!(!tasks.is_empty(), "no enabled task in {kind}");
+    let adb_cfg = config.adb_config().context("get adb config")?;
+
+    let id = instance.profile.clone();
+    spawn_blocking(move || maa_core::run_core_tauri(&id, tasks, adb_cfg))
+        .await
+        .context("join run")?
+}
+
+/// stop the run of instance `id`, defaults to the active profile.
+#[tauri::command]
+pub async fn stop_core(id: Option<String>, configs: State<'_, ConfigState>) -> CommandResult<()> {
+    let id = id.unwrap_or_else(|| configs.current().name().to_string());
+    registry::stop(&id).map_err(|e| log_error_context("send stop sign", e))
+}
+
+/// status of instance `id`, defaults to the active profile. `None` if it is
+/// not running.
+#[tauri::command]
+pub async fn run_status(
+    id: Option<String>,
+    configs: State<'_, ConfigState>,
+) -> CommandResult<Option<RunInfo>> {
+    let id = id.unwrap_or_else(|| configs.current().name().to_string());
+    Ok(registry::status(&id))
+}
+
+/// run state of instance `id`, defaults to the active profile.
+#[tauri::command]
+pub async fn get_run_state(
+    id: Option<String>,
+    configs: State<'_, ConfigState>,
+) -> CommandResult<RunState> {
+    let id = id.unwrap_or_else(|| configs.current().name().to_string());
+    Ok(state::get(&id))
+}
+
+#[tauri::command]
+pub async fn list_runs() -> CommandResult<Vec<RunInfo>> {
+    Ok(registry::list())
 }
 
 /// update config json with given  name and params.
@@ -37,19 +183,89 @@ pub async fn stop_core() -> CommandResult<()> {
 pub async fn update_config(
     name: String,
     params: Parameters,
-    configs: State<'_, Arc<Config>>,
+    configs: State<'_, ConfigState>,
 ) -> CommandResult<()> {
-    let cfg_type = name.parse().unwrap();
-    configs
+    let cfg_type = name
+        .parse()
+        .map_err(|e| log_error_context("update config", anyhow!("unknown config {e}")))?;
+    let config = configs.current();
+    config
         .set_and_write(cfg_type, params)
         .await
         .context("update config")
-        .map_err(|e| log_error_context("run daily", e))
+        .map_err(|e| log_error_context("update config", e))?;
+    if is_language_setting(&name) {
+        apply_language(&config);
+    }
+    Ok(())
+}
+
+/// add a task of `task_type` to its queue, returns the generated task id.
+#[tauri::command]
+pub async fn add_task(
+    task_type: String,
+    params: Parameters,
+    configs: State<'_, ConfigState>,
+) -> CommandResult<String> {
+    let task_type = task_type
+        .parse()
+        .map_err(|_| log_error_context("add task", anyhow!("unknown task type {task_type}")))?;
+    configs
+        .current()
+        .add_task(task_type, params)
+        .await
+        .map_err(|e| log_error_context("add task", e))
+}
+
+/// update task with given id, the task type can be omitted.
+#[tauri::command]
+pub async fn update_task(
+    id: String,
+    params: Parameters,
+    configs: State<'_, ConfigState>,
+) -> CommandResult<()> {
+    configs
+        .current()
+        .set_task(&id, params)
+        .await
+        .map_err(|e| log_error_context("update task", e))
 }
 
 #[tauri::command]
-pub async fn get_config(configs: State<'_, Arc<Config>>) -> CommandResult<Arc<Config>> {
-    Ok(configs.inner().clone())
+pub async fn remove_task(id: String, configs: State<'_, ConfigState>) -> CommandResult<()> {
+    configs
+        .current()
+        .remove_task(&id)
+        .await
+        .map_err(|e| log_error_context("remove task", e))
+}
+
+#[tauri::command]
+pub async fn get_config(configs: State<'_, ConfigState>) -> CommandResult<Arc<Config>> {
+    Ok(configs.current())
+}
+
+/// list backups of current profile, `config` filters by config name like
+/// `daily`.
+#[tauri::command]
+pub async fn list_backups(
+    config: Option<String>,
+    configs: State<'_, ConfigState>,
+) -> CommandResult<Vec<Backup>> {
+    configs
+        .current()
+        .list_backups(config.as_deref())
+        .await
+        .map_err(|e| log_error_context("list backups", e))
+}
+
+#[tauri::command]
+pub async fn restore_backup(name: String, configs: State<'_, ConfigState>) -> CommandResult<()> {
+    configs
+        .current()
+        .restore_backup(&name)
+        .await
+        .map_err(|e| log_error_context("restore backup", e))
 }
 
 #[tauri::command]
@@ -0,0 +1,75 @@
+use std::path::PathBuf;
+
+use maa_cfg::import::ImportReport;
+use tauri::State;
+
+use crate::{log_error_context, profile::ConfigState, CommandResult};
+
+/// import settings from MAA WPF `config/gui.json` into current profile.
+///
+/// `configuration` selects a WPF configuration, defaults to the one in use.
+#[tauri::command]
+pub async fn import_wpf_config(
+    path: PathBuf,
+    configuration: Option<String>,
+    configs: State<'_, ConfigState>,
+) -> CommandResult<ImportReport> {
+    configs
+        .current()
+        .import_wpf(&path, configuration.as_deref())
+        .await
+        .map_err(|e| log_error_context("import wpf config", e))
+}
+
+/// import a maa-cli task file (`tasks/*.toml` or `*.json`) into current
+/// profile.
+#[tauri::command]
+pub async fn import_maa_cli_tasks(
+    path: PathBuf,
+    configs: State<'_, ConfigState>,
+) -> CommandResult<ImportReport> {
+    configs
+        .current()
+        .import_maa_cli_tasks(&path)
+        .await
+        .map_err(|e| log_error_context("import maa-cli tasks", e))
+}
+
+/// export enabled tasks of current profile as a maa-cli task file.
+#[tauri::command]
+pub async fn export_maa_cli_tasks(
+    path: PathBuf,
+    configs: State<'_, ConfigState>,
+) -> CommandResult<()> {
+    configs
+        .current()
+        .export_maa_cli_tasks(&path)
+        .await
+        .map_err(|e| log_error_context("export maa-cli tasks", e))
+}
+
+/// import connection settings from a maa-cli `profiles/*.toml`.
+#[tauri::command]
+pub async fn import_maa_cli_profile(
+    path: PathBuf,
+    configs: State<'_, ConfigState>,
+) -> CommandResult<ImportReport> {
+    configs
+        .current()
+        .import_maa_cli_profile(&path)
+        .await
+        .map_err(|e| log_error_context("import maa-cli profile", e))
+}
+
+/// export adb settings of current profile as a maa-cli profile.
+#[tauri::command]
+pub async fn export_maa_cli_profile(
+    path: PathBuf,
+    configs: State<'_, ConfigState>,
+) -> CommandResult<ImportReport> {
+    configs
+        .current()
+        .export_maa_cli_profile(&path)
+        .await
+        .map_err(|e| log_error_context("export maa-cli profile", e))
+}
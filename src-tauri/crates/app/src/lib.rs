@@ -1,24 +1,53 @@
 #![deny(warnings)]
 
 mod core;
+mod import;
+mod profile;
+mod remote;
+mod scheduler;
+mod tools;
 mod updater;
+mod watcher;
 
-use core::{get_config, run_daily, set_log_level, stop_core, update_config};
-use std::{env::set_current_dir, sync::Arc, time::Duration};
+use core::{
+    add_task, get_config, get_run_state, list_backups, list_runs, remove_task, restore_backup,
+    run_batch, run_daily, run_queue, run_status, set_log_level, stop_core, update_config,
+    update_task,
+};
+use std::{env::set_current_dir, time::Duration};
 
 use anyhow::Context;
+use import::{
+    export_maa_cli_profile, export_maa_cli_tasks, import_maa_cli_profile, import_maa_cli_tasks,
+    import_wpf_config,
+};
 use log::error;
 use log4rs::{init_config, Handle};
-use maa_cfg::Config;
-use maa_core::tauri_logger::log_config;
+use maa_cfg::{profile::active_profile, Config};
+use maa_core::{tauri_logger::log_config, tools::ToolResult};
 use maa_updater::{
     download_reporter::DefaultDownloadReporter, updater::Updater, version::Versions,
 };
-use tauri::{utils::platform::current_exe, AppHandle, Emitter, Manager};
+use profile::{
+    activate_profile, clone_profile, create_profile, delete_profile, list_profiles, rename_profile,
+    ConfigState,
+};
+use remote::init_remote;
+use scheduler::{init_scheduler, list_scheduled_runs};
+use tauri::{async_runtime::spawn, utils::platform::current_exe, AppHandle, Emitter, Manager};
+use tools::{
+    calculate_recruit, depot_history, diff_oper_box, export_depot, export_oper_box,
+    export_stage_drops, oper_box_history, recruit_summary, save_tool_result, stage_drop_stats,
+};
 use updater::{update, update_resource, VersionState};
+use watcher::init_config_watcher;
 
 use crate::updater::UPDATE_REPORT_EVENT;
 
+/// payload 为 `(实例 id, RunState)`
+const RUN_STATE_EVENT: &str = "run-state";
+/// payload 为 `(实例 id, MaaEvent)`
+const MAA_EVENT: &str = "maa-event";
 const DOWNLOAD_REPORT_INTERVAL: Duration = Duration::from_millis(500);
 
 pub(crate) type CommandResult<T> = Result<T, ()>;
@@ -27,26 +56,66 @@ pub(crate) type CommandResult<T> = Result<T, ()>;
 pub async fn run() -> anyhow::Result<()> {
     init_cwd()?;
     // init states
-    let config_state = Config::load(None).await.context("load configs")?;
+    let profile = active_profile().await.context("get active profile")?;
+    let config_state = Config::load(Some(profile)).await.context("load configs")?;
     let version_state = Versions::load().context("load versions")?;
     // build app
     tauri::Builder::default()
         .plugin(tauri_plugin_opener::init())
-        .manage(Arc::new(config_state))
+        .manage(ConfigState::new(config_state))
         .manage(VersionState::new(version_state))
         .setup(|app| {
             app.manage(init_log(app.handle().clone())?);
             app.manage(init_updater(app.handle().clone()));
+            app.manage(init_config_watcher(app.handle().clone())?);
+            init_scheduler(app.handle().clone());
+            init_run_state_event(app.handle().clone());
+            init_maa_event(app.handle().clone());
+            let config = app.state::<ConfigState>().current();
+            if let Err(e) = init_remote(app.handle().clone(), &config) {
+                log_error_context("init remote control", e);
+            }
             Ok(())
         })
         .invoke_handler(tauri::generate_handler![
             run_daily,
+            run_queue,
+            run_batch,
             stop_core,
+            run_status,
+            get_run_state,
+            list_runs,
             update_config,
+            add_task,
+            update_task,
+            remove_task,
             get_config,
+            list_backups,
+            restore_backup,
             set_log_level,
             update,
-            update_resource
+            update_resource,
+            list_profiles,
+            create_profile,
+            clone_profile,
+            rename_profile,
+            delete_profile,
+            activate_profile,
+            import_wpf_config,
+            import_maa_cli_tasks,
+            export_maa_cli_tasks,
+            import_maa_cli_profile,
+            export_maa_cli_profile,
+            list_scheduled_runs,
+            depot_history,
+            export_depot,
+            oper_box_history,
+            diff_oper_box,
+            export_oper_box,
+            stage_drop_stats,
+            export_stage_drops,
+            recruit_summary,
+            calculate_recruit
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
@@ -58,6 +127,35 @@ fn init_log(handle: AppHandle) -> anyhow::Result<Handle> {
     init_config(config).context("init log with config")
 }
 
+fn init_run_state_event(handle: AppHandle) {
+    maa_core::state::set_listener(move |instance, state| {
+        if let Err(e) = handle.emit(RUN_STATE_EVENT, (instance, state)) {
+            error!("Failed to emit run state: {}", e);
+        }
+    });
+}
+
+fn init_maa_event(handle: AppHandle) {
+    maa_callback::event::set_listener(move |instance, event| {
+        if let Err(e) = handle.emit(MAA_EVENT, (instance, event)) {
+            error!("Failed to emit maa event: {}", e);
+        }
+        match ToolResult::from_event(instance, event) {
+            Ok(Some(result)) => {
+                let handle = handle.clone();
+                let instance = instance.to_string();
+                spawn(async move {
+                    if let Err(e) = save_tool_result(&handle, instance, result).await {
+                        log_error_context("save tool result", e);
+                    }
+                });
+            }
+            Ok(None) => {}
+            Err(e) => log_error_context("get tool result", e),
+        }
+    });
+}
+
 fn init_cwd() -> anyhow::Result<()> {
     let exe = current_exe().context("get exe path")?;
     set_current_dir(exe.parent().unwrap()).context("set cwd")
@@ -0,0 +1,157 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, RwLock},
+};
+
+use anyhow::Context;
+use log::warn;
+use maa_callback::i18n;
+use maa_cfg::{
+    profile::{self, Profiles},
+    settings::SettingType,
+    Config,
+};
+use tauri::State;
+use tokio::sync::Mutex;
+
+use crate::{log_error_context, CommandResult};
+
+/// 当前激活的配置组，切换时整体替换
+pub struct ConfigState {
+    active: RwLock<Arc<Config>>,
+    /// 用到过的其他配置组
+    ///
+    /// 每个配置组只保留一份 `Config`，并发保存时才能共用其中的锁和磁盘摘要
+    others: Mutex<HashMap<String, Arc<Config>>>,
+}
+
+impl ConfigState {
+    pub fn new(config: Config) -> Self {
+        apply_language(&config);
+        Self {
+            active: RwLock::new(Arc::new(config)),
+            others: Mutex::default(),
+        }
+    }
+
+    pub fn current(&self) -> Arc<Config> {
+        self.active.read().unwrap().clone()
+    }
+
+    /// 指定配置组的配置，其他配置组首次使用时从磁盘加载
+    pub(crate) async fn profile(&self, name: String) -> anyhow::Result<Arc<Config>> {
+        // 持有锁再比较，避免与切换配置组交错而加载出第二份
+        let mut others = self.others.lock().await;
+        let active = self.current();
+        if active.name() == name {
+            return Ok(active);
+        }
+        if let Some(config) = others.get(&name) {
+            return Ok(config.clone());
+        }
+        let config = Config::load(Some(name.clone()))
+            .await
+            .context("load configs")?;
+        let config = Arc::new(config);
+        others.insert(name, config.clone());
+        Ok(config)
+    }
+
+    /// 丢弃缓存的配置组，用于改名或删除后
+    async fn forget(&self, name: &str) {
+        self.others.lock().await.remove(name);
+    }
+
+    async fn replace(&self, config: Config) -> Arc<Config> {
+        apply_language(&config);
+        let config = Arc::new(config);
+        let mut others = self.others.lock().await;
+        others.remove(config.name());
+        let old = std::mem::replace(&mut *self.active.write().unwrap(), config.clone());
+        if old.name() != config.name() {
+            others.insert(old.name().to_string(), old);
+        }
+        config
+    }
+}
+
+#[tauri::command]
+pub async fn list_profiles() -> CommandResult<Profiles> {
+    profile::list_profiles()
+        .await
+        .map_err(|e| log_error_context("list profiles", e))
+}
+
+#[tauri::command]
+pub async fn create_profile(name: String) -> CommandResult<()> {
+    profile::create_profile(&name)
+        .await
+        .map_err(|e| log_error_context("create profile", e))
+}
+
+#[tauri::command]
+pub async fn clone_profile(from: String, to: String) -> CommandResult<()> {
+    profile::clone_profile(&from, &to)
+        .await
+        .map_err(|e| log_error_context("clone profile", e))
+}
+
+#[tauri::command]
+pub async fn rename_profile(
+    from: String,
+    to: String,
+    configs: State<'_, ConfigState>,
+) -> CommandResult<()> {
+    profile::rename_profile(&from, &to)
+        .await
+        .map_err(|e| log_error_context("rename profile", e))?;
+    if configs.current().name() == from {
+        // 目录已经变更，需要按新名字重新加载
+        load_and_replace(to, &configs)
+            .await
+            .map_err(|e| log_error_context("reload renamed profile", e))?;
+    }
+    configs.forget(&from).await;
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn delete_profile(name: String, configs: State<'_, ConfigState>) -> CommandResult<()> {
+    profile::delete_profile(&name)
+        .await
+        .map_err(|e| log_error_context("delete profile", e))?;
+    configs.forget(&name).await;
+    Ok(())
+}
+
+/// 切换到指定配置组，返回新的配置
+#[tauri::command]
+pub async fn activate_profile(
+    name: String,
+    configs: State<'_, ConfigState>,
+) -> CommandResult<Arc<Config>> {
+    profile::set_active_profile(&name)
+        .await
+        .map_err(|e| log_error_context("activate profile", e))?;
+    load_and_replace(name, &configs)
+        .await
+        .map_err(|e| log_error_context("load profile", e))
+}
+
+/// 按配置组的语言设置切换回调消息的语言，设置无效时保持不变
+pub(crate) fn apply_language(config: &Config) {
+    match config.language_config().and_then(|s| s.language()) {
+        Ok(language) => i18n::set_language(language),
+        Err(e) => warn!("apply language failed: {e:?}"),
+    }
+}
+
+/// 修改的是否为语言设置
+pub(crate) fn is_language_setting(name: &str) -> bool {
+    name == SettingType::Language.as_ref()
+}
+
+async fn load_and_replace(name: String, configs: &ConfigState) -> anyhow::Result<Arc<Config>> {
+    let config = Config::load(Some(name)).await.context("load configs")?;
+    Ok(configs.replace(config).await)
+}
@@ -0,0 +1,312 @@
+//! 可选的本地 HTTP/WebSocket 控制接口，供脚本和其他设备在不打开窗口时使用
+//!
+//! 所有请求都需要在 `Authorization: Bearer <token>` 或 `?token=` 中携带 token
+
+use std::{net::SocketAddr, sync::Arc};
+
+use anyhow::{anyhow, Context};
+use axum::{
+    extract::{
+        ws::{Message, WebSocket, WebSocketUpgrade},
+        Path, Query, Request, State,
+    },
+    http::{header::AUTHORIZATION, StatusCode},
+    middleware::{self, Next},
+    response::{IntoResponse, Response},
+    routing::{get, post, put},
+    Json, Router,
+};
+use log::{error, info, warn};
+use maa_callback::callback_types::ResultEntry;
+use maa_cfg::{watch::ConflictError, Config, ConfigType, Parameters};
+use maa_core::{
+    registry::{self, RunInfo},
+    state::{self, RunState},
+    tauri_logger::CALLBACK_EVENT,
+    tools::recruit_calc::Recruitment,
+};
+use maa_updater::{
+    download_reporter::DefaultDownloadReporter,
+    updater::{UpdateResult, Updater},
+    version::ClientVersionRequest,
+};
+use serde::Deserialize;
+use serde_json::Value;
+use tauri::{async_runtime::spawn, AppHandle, Listener, Manager};
+use tokio::{
+    net::TcpListener,
+    select,
+    sync::broadcast::{self, error::RecvError},
+};
+
+use crate::{
+    core::{run_instance, BatchInstance},
+    log_error_context,
+    profile::{apply_language, is_language_setting, ConfigState},
+    updater::{update_client, update_resource_files, VersionState},
+};
+
+/// 未被读取的日志超过该数量时，慢的连接会丢弃旧日志
+const LOG_CHANNEL_CAPACITY: usize = 256;
+
+#[derive(Clone)]
+struct Remote {
+    handle: AppHandle,
+    token: Arc<str>,
+    /// `callback-log` 事件的 payload
+    logs: broadcast::Sender<String>,
+}
+
+/// 按当前配置组的 `Remote` 设置启动，未开启时不做任何事
+pub fn init_remote(handle: AppHandle, config: &Config) -> anyhow::Result<()> {
+    let settings = config.remote_config()?;
+    if !settings.enable {
+        return Ok(());
+    }
+    settings.validate()?;
+    let addr = settings.socket_addr()?;
+
+    let (logs, _) = broadcast::channel(LOG_CHANNEL_CAPACITY);
+    let sender = logs.clone();
+    handle.listen_any(CALLBACK_EVENT, move |event| {
+        // 没有连接时发送失败，直接丢弃
+        let _ = sender.send(event.payload().to_string());
+    });
+
+    let remote = Remote {
+        handle,
+        token: settings.token.into(),
+        logs,
+    };
+    spawn(async move {
+        if let Err(e) = serve(remote, addr).await {
+            error!("remote control stopped: {e:?}");
+        }
+    });
+    Ok(())
+}
+
+async fn serve(remote: Remote, addr: SocketAddr) -> anyhow::Result<()> {
+    let app = Router::new()
+        .route("/runs", get(list_runs).post(start_run))
+        .route("/runs/{id}", get(run_state))
+        .route("/runs/{id}/stop", post(stop_run))
+        .route("/config", get(get_config))
+        .route("/config/{name}", put(update_config))
+        .route("/update/core", post(update_core))
+        .route("/update/resource", post(update_resource))
+        .route("/recruit", post(calculate_recruit))
+        .route("/logs", get(stream_logs))
+        .layer(middleware::from_fn_with_state(remote.clone(), authorize))
+        .with_state(remote);
+
+    let listener = TcpListener::bind(addr)
+        .await
+        .with_context(|| format!("bind {addr}"))?;
+    info!("remote control listening on {addr}");
+    axum::serve(listener, app).await.context("serve")
+}
+
+struct ApiError(StatusCode, anyhow::Error);
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        warn!("remote request failed: {:#}", self.1);
+        (self.0, format!("{:#}", self.1)).into_response()
+    }
+}
+
+impl<E: Into<anyhow::Error>> From<E> for ApiError {
+    fn from(e: E) -> Self {
+        Self(StatusCode::INTERNAL_SERVER_ERROR, e.into())
+    }
+}
+
+type ApiResult<T> = Result<T, ApiError>;
+
+#[derive(Deserialize)]
+struct TokenQuery {
+    token: Option<String>,
+}
+
+/// 浏览器中的 WebSocket 无法设置请求头，因此也接受 query 中的 token
+async fn authorize(
+    State(remote): State<Remote>,
+    Query(query): Query<TokenQuery>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let bearer = request
+        .headers()
+        .get(AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "));
+    if bearer.or(query.token.as_deref()) != Some(&*remote.token) {
+        return StatusCode::UNAUTHORIZED.into_response();
+    }
+    next.run(request).await
+}
+
+async fn list_runs() -> Json<Vec<RunInfo>> {
+    Json(registry::list())
+}
+
+async fn run_state(Path(id): Path<String>) -> Json<RunState> {
+    Json(state::get(&id))
+}
+
+#[derive(Deserialize)]
+struct RunRequest {
+    /// 默认为当前配置组
+    profile: Option<String>,
+    /// 见 `run_queue`，默认为 `daily`
+    queue: Option<String>,
+}
+
+/// 在后台运行并立即返回，结果通过运行状态查询
+async fn start_run(
+    State(remote): State<Remote>,
+    Json(request): Json<RunRequest>,
+) -> ApiResult<StatusCode> {
+    let handle = remote.handle.clone();
+    let active = handle.state::<ConfigState>().current();
+    let instance = BatchInstance {
+        profile: request.profile.unwrap_or_else(|| active.name().to_string()),
+        queue: request.queue,
+    };
+    if registry::is_running(&instance.profile) {
+        return Err(ApiError(
+            StatusCode::CONFLICT,
+            anyhow!("instance {} is already running", instance.profile),
+        ));
+    }
+    spawn(async move {
+        if let Err(e) = run_instance(&handle, &instance).await {
+            log_error_context(&format!("run {}", instance.profile), e);
+        }
+    });
+    Ok(StatusCode::ACCEPTED)
+}
+
+async fn stop_run(Path(id): Path<String>) -> ApiResult<StatusCode> {
+    registry::stop(&id).map_err(|e| ApiError(StatusCode::NOT_FOUND, e))?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+async fn get_config(State(remote): State<Remote>) -> ApiResult<Json<Value>> {
+    let config = remote.handle.state::<ConfigState>().current();
+    Ok(Json(serde_json::to_value(&*config)?))
+}
+
+/// 与 `update_config` 命令相同，参数校验失败时返回 400
+async fn update_config(
+    State(remote): State<Remote>,
+    Path(name): Path<String>,
+    Json(params): Json<Parameters>,
+) -> ApiResult<StatusCode> {
+    let cfg_type = parse_config_name(&name)?;
+    let config = remote.handle.state::<ConfigState>().current();
+    config.set_and_write(cfg_type, params).await.map_err(|e| {
+        let status = if e.chain().any(|c| c.is::<ConflictError>()) {
+            StatusCode::CONFLICT
+        } else {
+            StatusCode::BAD_REQUEST
+        };
+        ApiError(status, e)
+    })?;
+    if is_language_setting(&name) {
+        apply_language(&config);
+    }
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// 配置名来自请求路径，未知的配置返回 400
+fn parse_config_name(name: &str) -> ApiResult<ConfigType> {
+    name.parse()
+        .map_err(|e| ApiError(StatusCode::BAD_REQUEST, anyhow!("unknown config {e}")))
+}
+
+#[derive(Deserialize)]
+struct UpdateRequest {
+    channel: ClientVersionRequest,
+}
+
+async fn update_core(
+    State(remote): State<Remote>,
+    Json(request): Json<UpdateRequest>,
+) -> ApiResult<Json<UpdateResult>> {
+    let updater = remote.handle.state::<Updater<DefaultDownloadReporter>>();
+    let versions = remote.handle.state::<VersionState>();
+    Ok(Json(
+        update_client(request.channel, &updater, &versions).await?,
+    ))
+}
+
+async fn update_resource(State(remote): State<Remote>) -> ApiResult<Json<UpdateResult>> {
+    let updater = remote.handle.state::<Updater<DefaultDownloadReporter>>();
+    let versions = remote.handle.state::<VersionState>();
+    Ok(Json(update_resource_files(&updater, &versions).await?))
+}
+
+#[derive(Deserialize)]
+struct RecruitRequest {
+    tags: Vec<String>,
+}
+
+/// 不需要模拟器，手机上也可以直接使用
+async fn calculate_recruit(
+    Json(request): Json<RecruitRequest>,
+) -> ApiResult<Json<Vec<ResultEntry>>> {
+    let results = Recruitment::load()
+        .and_then(|r| r.calculate(&request.tags))
+        .map_err(|e| ApiError(StatusCode::BAD_REQUEST, e))?;
+    Ok(Json(results))
+}
+
+/// 推送 `callback-log` 事件的内容
+async fn stream_logs(State(remote): State<Remote>, ws: WebSocketUpgrade) -> Response {
+    let logs = remote.logs.subscribe();
+    ws.on_upgrade(move |socket| forward_logs(socket, logs))
+}
+
+async fn forward_logs(mut socket: WebSocket, mut logs: broadcast::Receiver<String>) {
+    loop {
+        select! {
+            log = logs.recv() => match log {
+                Ok(log) => {
+                    if socket.send(Message::Text(log.into())).await.is_err() {
+                        break;
+                    }
+                }
+                Err(RecvError::Lagged(n)) => warn!("remote log stream skipped {n} messages"),
+                Err(RecvError::Closed) => break,
+            },
+            msg = socket.recv() => match msg {
+                Some(Ok(Message::Close(_)) | Err(_)) | None => break,
+                _ => {}
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use maa_cfg::TaskType;
+
+    use super::*;
+
+    #[test]
+    fn reject_unknown_config() {
+        let err = parse_config_name("foo").unwrap_err();
+        assert_eq!(err.0, StatusCode::BAD_REQUEST);
+        assert_eq!(err.1.to_string(), "unknown config foo");
+        assert!(matches!(
+            parse_config_name("Remote"),
+            Ok(ConfigType::Settings(_))
+        ));
+        assert!(matches!(
+            parse_config_name("custom:foo"),
+            Ok(ConfigType::Task(TaskType::Custom(name))) if name == "foo"
+        ));
+    }
+}
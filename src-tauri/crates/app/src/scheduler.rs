@@ -0,0 +1,166 @@
+use std::{sync::Arc, time::Duration};
+
+use anyhow::{ensure, Context};
+use chrono::{DateTime, Local, TimeDelta};
+use log::{info, trace, warn};
+use maa_cfg::{
+    profile,
+    schedule::{self, ScheduleSettings, Trigger},
+    Config,
+};
+use serde::Serialize;
+use tauri::{
+    async_runtime::{spawn, spawn_blocking},
+    AppHandle, Manager, State,
+};
+
+use crate::{log_error_context, profile::ConfigState, CommandResult};
+
+/// 检查触发器的间隔
+const TICK: Duration = Duration::from_secs(30);
+/// 超过该时长才检查到的触发视为错过（如系统休眠），只记录不补跑
+const MISSED_GRACE: TimeDelta = TimeDelta::minutes(2);
+
+#[derive(Debug, Serialize)]
+pub struct ScheduledRun {
+    pub profile: String,
+    pub queue: String,
+    pub next: Option<DateTime<Local>>,
+}
+
+/// 后台检查所有配置组的定时触发器
+pub fn init_scheduler(handle: AppHandle) {
+    spawn(async move {
+        let mut last = Local::now();
+        loop {
+            tokio::time::sleep(TICK).await;
+            let now = Local::now();
+            if let Err(e) = check_triggers(&handle, last, now).await {
+                log_error_context("check schedule", e);
+            }
+            last = now;
+        }
+    });
+}
+
+async fn check_triggers(
+    handle: &AppHandle,
+    after: DateTime<Local>,
+    until: DateTime<Local>,
+) -> anyhow::Result<()> {
+    trace!("check schedule in ({after}, {until}]");
+    let configs = handle.state::<ConfigState>();
+    let active = configs.current();
+    for name in profile::list_profiles().await?.profiles {
+        let schedule = match profile_schedule(&active, &name).await {
+            Ok(s) => s,
+            Err(e) => {
+                warn!("skip schedule of profile {name}: {e:?}");
+                continue;
+            }
+        };
+        if !schedule.enable {
+            continue;
+        }
+        let mut due = vec![];
+        for trigger in schedule.triggers.into_iter().filter(|t| t.enable) {
+            let times = match trigger.when.fire_times(after, until) {
+                Ok(t) => t,
+                Err(e) => {
+                    warn!("invalid trigger in profile {name}: {e:?}");
+                    continue;
+                }
+            };
+            let Some(latest) = times.last() else {
+                continue;
+            };
+            for missed in times.iter().filter(|t| until - **t > MISSED_GRACE) {
+                warn!(
+                    "missed scheduled run of {name}/{} at {missed}",
+                    trigger.queue
+                );
+            }
+            if until - *latest <= MISSED_GRACE {
+                due.push(trigger);
+            }
+        }
+        if due.is_empty() {
+            continue;
+        }
+        // 只有到点时才加载完整配置
+        let config = match configs.profile(name.clone()).await {
+            Ok(c) => c,
+            Err(e) => {
+                warn!("skip schedule of profile {name}: {e:?}");
+                continue;
+            }
+        };
+        for trigger in &due {
+            fire(config.clone(), trigger);
+        }
+    }
+    Ok(())
+}
+
+/// 当前激活的配置组使用缓存，其他配置组只读取定时设置
+///
+/// 避免每次检查都加载并迁移全部配置
+async fn profile_schedule(active: &Config, name: &str) -> anyhow::Result<ScheduleSettings> {
+    if active.name() == name {
+        active.schedule_config()
+    } else {
+        schedule::load_schedule(name).await
+    }
+}
+
+fn fire(config: Arc<Config>, trigger: &Trigger) {
+    let profile = config.name().to_string();
+    let queue = trigger.queue.clone();
+    if maa_core::registry::is_running(&profile) {
+        warn!("skip scheduled run of {profile}/{queue}: already running");
+        return;
+    }
+    info!("scheduled run of {profile}/{queue}");
+    spawn(async move {
+        let context = format!("scheduled run of {profile}/{queue}");
+        let run = async {
+            let tasks = config.task_queue(&queue.parse().unwrap())?;
+            ensure!(!tasks.is_empty(), "no enabled task in {queue}");
+            let adb_cfg = config.adb_config().context("get adb config")?;
+            spawn_blocking(move || maa_core::run_core_tauri(&profile, tasks, adb_cfg))
+                .await
+                .context("join run")?
+        };
+        if let Err(e) = run.await {
+            log_error_context(&context, e);
+        }
+    });
+}
+
+/// list the next run of each enabled trigger in all profiles.
+#[tauri::command]
+pub async fn list_scheduled_runs(
+    configs: State<'_, ConfigState>,
+) -> CommandResult<Vec<ScheduledRun>> {
+    let list = async {
+        let active = configs.current();
+        let now = Local::now();
+        let mut runs = vec![];
+        for name in profile::list_profiles().await?.profiles {
+            let schedule = profile_schedule(&active, &name).await?;
+            if !schedule.enable {
+                continue;
+            }
+            for trigger in schedule.triggers.into_iter().filter(|t| t.enable) {
+                runs.push(ScheduledRun {
+                    profile: name.clone(),
+                    next: trigger.when.next_fire(now)?,
+                    queue: trigger.queue,
+                });
+            }
+        }
+        anyhow::Ok(runs)
+    };
+    list.await
+        .map_err(|e| log_error_context("list scheduled runs", e))
+}
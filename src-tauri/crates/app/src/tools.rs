@@ -0,0 +1,171 @@
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{anyhow, Context};
+use chrono::{DateTime, Local};
+use maa_callback::callback_types::ResultEntry;
+use maa_cfg::ledger::{stage_drop_stats_csv, RecruitSummary, StageDropStats};
+use maa_core::tools::{
+    depot::{self, DepotFormat, DepotRecord},
+    oper_box::{self, OperBoxDiff, OperBoxFormat, OperBoxRecord},
+    recruit_calc::Recruitment,
+    ToolResult,
+};
+use tauri::{AppHandle, Manager, State};
+
+use crate::{log_error_context, profile::ConfigState, CommandResult};
+
+/// 保存到实例对应配置组的 `tool-storage.json`
+pub(crate) async fn save_tool_result(
+    handle: &AppHandle,
+    instance: String,
+    result: ToolResult,
+) -> anyhow::Result<()> {
+    let config = handle.state::<ConfigState>().profile(instance).await?;
+    result.save(&config).await
+}
+
+/// depot recognition results of current profile, oldest first.
+#[tauri::command]
+pub async fn depot_history(configs: State<'_, ConfigState>) -> CommandResult<Vec<DepotRecord>> {
+    depot::depot_history(&configs.current()).map_err(|e| log_error_context("get depot history", e))
+}
+
+/// export the latest depot of current profile, `format` is one of
+/// `arkplanner`, `lolicon` and `csv`.
+#[tauri::command]
+pub async fn export_depot(
+    format: String,
+    path: PathBuf,
+    configs: State<'_, ConfigState>,
+) -> CommandResult<()> {
+    export_depot_impl(&format, &path, &configs)
+        .await
+        .map_err(|e| log_error_context("export depot", e))
+}
+
+async fn export_depot_impl(format: &str, path: &Path, configs: &ConfigState) -> anyhow::Result<()> {
+    let format: DepotFormat = format
+        .parse()
+        .map_err(|_| anyhow!("unknown depot format: {format}"))?;
+    let record = depot::latest_depot(&configs.current())?
+        .context("no depot recognition result, run Depot first")?;
+    let contents = format.export(&record.depot)?;
+    tokio::fs::write(path, contents)
+        .await
+        .with_context(|| format!("write {path:?}"))
+}
+
+/// operator box recognition results of current profile, oldest first.
+#[tauri::command]
+pub async fn oper_box_history(
+    configs: State<'_, ConfigState>,
+) -> CommandResult<Vec<OperBoxRecord>> {
+    oper_box::oper_box_history(&configs.current())
+        .map_err(|e| log_error_context("get oper box history", e))
+}
+
+/// diff two operator box snapshots by their index in the history,
+/// defaults to the latest two.
+#[tauri::command]
+pub async fn diff_oper_box(
+    from: Option<usize>,
+    to: Option<usize>,
+    configs: State<'_, ConfigState>,
+) -> CommandResult<OperBoxDiff> {
+    oper_box::diff_oper_box(&configs.current(), from, to)
+        .map_err(|e| log_error_context("diff oper box", e))
+}
+
+/// export owned operators of the latest snapshot, `format` is `json` or `csv`.
+#[tauri::command]
+pub async fn export_oper_box(
+    format: String,
+    path: PathBuf,
+    configs: State<'_, ConfigState>,
+) -> CommandResult<()> {
+    export_oper_box_impl(&format, &path, &configs)
+        .await
+        .map_err(|e| log_error_context("export oper box", e))
+}
+
+async fn export_oper_box_impl(
+    format: &str,
+    path: &Path,
+    configs: &ConfigState,
+) -> anyhow::Result<()> {
+    let format: OperBoxFormat = format
+        .parse()
+        .map_err(|_| anyhow!("unknown oper box format: {format}"))?;
+    let record = oper_box::oper_box_history(&configs.current())?
+        .pop()
+        .context("no oper box recognition result, run OperBox first")?;
+    let contents = format.export(&record.oper_box)?;
+    tokio::fs::write(path, contents)
+        .await
+        .with_context(|| format!("write {path:?}"))
+}
+
+/// drop statistics per stage of current profile within `[from, to)`,
+/// `ap_costs` is the sanity cost of stages whose records lack it.
+#[tauri::command]
+pub async fn stage_drop_stats(
+    from: Option<DateTime<Local>>,
+    to: Option<DateTime<Local>>,
+    ap_costs: Option<HashMap<String, u32>>,
+    configs: State<'_, ConfigState>,
+) -> CommandResult<Vec<StageDropStats>> {
+    configs
+        .current()
+        .stage_drop_stats(from, to, &ap_costs.unwrap_or_default())
+        .await
+        .map_err(|e| log_error_context("get stage drop stats", e))
+}
+
+/// export drop statistics as csv, arguments are the same as `stage_drop_stats`.
+#[tauri::command]
+pub async fn export_stage_drops(
+    path: PathBuf,
+    from: Option<DateTime<Local>>,
+    to: Option<DateTime<Local>>,
+    ap_costs: Option<HashMap<String, u32>>,
+    configs: State<'_, ConfigState>,
+) -> CommandResult<()> {
+    let export = async {
+        let stats = configs
+            .current()
+            .stage_drop_stats(from, to, &ap_costs.unwrap_or_default())
+            .await?;
+        tokio::fs::write(&path, stage_drop_stats_csv(&stats)?)
+            .await
+            .with_context(|| format!("write {path:?}"))
+    };
+    export
+        .await
+        .map_err(|e| log_error_context("export stage drops", e))
+}
+
+/// recruitment statistics of current profile within `[from, to)`, in total
+/// and per day.
+#[tauri::command]
+pub async fn recruit_summary(
+    from: Option<DateTime<Local>>,
+    to: Option<DateTime<Local>>,
+    configs: State<'_, ConfigState>,
+) -> CommandResult<RecruitSummary> {
+    configs
+        .current()
+        .recruit_summary(from, to)
+        .await
+        .map_err(|e| log_error_context("get recruit summary", e))
+}
+
+/// calculate recruitment results offline with `resource/recruitment.json`.
+#[tauri::command]
+pub async fn calculate_recruit(tags: Vec<String>) -> CommandResult<Vec<ResultEntry>> {
+    Recruitment::load()
+        .and_then(|r| r.calculate(&tags))
+        .map_err(|e| log_error_context("calculate recruit", e))
+}
@@ -1,5 +1,6 @@
 use std::{env::current_dir, ops::Deref, sync::RwLock};
 
+use anyhow::Context;
 use maa_core::reload_core;
 use maa_updater::{
     download_reporter::DefaultDownloadReporter,
@@ -34,30 +35,9 @@ pub async fn update(
     updater: State<'_, Updater<DefaultDownloadReporter>>,
     versions: State<'_, VersionState>,
 ) -> CommandResult<UpdateResult> {
-    let dst = current_dir().map_err(|e| log_error_context("获取CWD", e))?;
-    let ver = versions.read().unwrap().client.clone();
-    match updater.update(ver, target_type, &dst).await {
-        Ok(res) => {
-            if let UpdateResult::ClientSuccess(v) = &res {
-                let mut guard = versions.write().unwrap();
-                guard.client = v.clone();
-                guard
-                    .client
-                    .write()
-                    .map_err(|e| log_error_context("写入客户端配置", e))?;
-                guard
-                    .resource
-                    .reload()
-                    .map_err(|e| log_error_context("写入资源配置", e))?;
-                reload_core().map_err(|e| log_error_context("重启MaaCore", e))?;
-            }
-            Ok(res)
-        }
-        Err(e) => {
-            log_error_context("升级客户端", e);
-            Err(())
-        }
-    }
+    update_client(target_type, &updater, &versions)
+        .await
+        .map_err(|e| log_error_context("升级客户端", e))
 }
 
 #[tauri::command]
@@ -65,15 +45,41 @@ pub async fn update_resource(
     updater: State<'_, Updater<DefaultDownloadReporter>>,
     versions: State<'_, VersionState>,
 ) -> CommandResult<UpdateResult> {
-    let dst = current_dir().map_err(|e| log_error_context("获取CWD", e))?;
-    let ver = versions.read().unwrap().resource.clone();
-    updater
-        .update_resource(ver, &dst)
+    update_resource_files(&updater, &versions)
         .await
-        .inspect(|res| {
-            if let UpdateResult::ResourceSuccess(v) = res {
-                versions.write().unwrap().resource = v.clone();
-            }
-        })
         .map_err(|e| log_error_context("升级资源", e))
 }
+
+/// 更新客户端，成功后重启 MaaCore
+pub(crate) async fn update_client(
+    target_type: ClientVersionRequest,
+    updater: &Updater<DefaultDownloadReporter>,
+    versions: &VersionState,
+) -> anyhow::Result<UpdateResult> {
+    let dst = current_dir().context("获取CWD")?;
+    let ver = versions.read().unwrap().client.clone();
+    let res = updater.update(ver, target_type, &dst).await?;
+    if let UpdateResult::ClientSuccess(v) = &res {
+        {
+            let mut guard = versions.write().unwrap();
+            guard.client = v.clone();
+            guard.client.write().context("写入客户端配置")?;
+            guard.resource.reload().context("写入资源配置")?;
+        }
+        reload_core().context("重启MaaCore")?;
+    }
+    Ok(res)
+}
+
+pub(crate) async fn update_resource_files(
+    updater: &Updater<DefaultDownloadReporter>,
+    versions: &VersionState,
+) -> anyhow::Result<UpdateResult> {
+    let dst = current_dir().context("获取CWD")?;
+    let ver = versions.read().unwrap().resource.clone();
+    let res = updater.update_resource(ver, &dst).await?;
+    if let UpdateResult::ResourceSuccess(v) = &res {
+        versions.write().unwrap().resource = v.clone();
+    }
+    Ok(res)
+}
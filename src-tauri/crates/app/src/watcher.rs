@@ -0,0 +1,50 @@
+use std::sync::Mutex;
+
+use anyhow::Context;
+use log::{info, warn};
+use maa_cfg::{
+    watch::{self, RecommendedWatcher},
+    SETTINGS_CFG,
+};
+use tauri::{async_runtime::spawn, AppHandle, Emitter, Manager};
+
+use crate::profile::{apply_language, ConfigState};
+
+/// 外部修改的配置重新加载后发送，payload 为 `maa_cfg::watch::ConfigChange`
+pub const CONFIG_CHANGED_EVENT: &str = "config-changed";
+
+/// 持有 watcher，drop 后停止监听
+pub struct ConfigWatcher {
+    _watcher: Mutex<RecommendedWatcher>,
+}
+
+/// 只重新加载当前激活的配置组，其余配置组在切换时会重新读取
+pub fn init_config_watcher(handle: AppHandle) -> anyhow::Result<ConfigWatcher> {
+    let watcher = watch::watch(move |change| {
+        let handle = handle.clone();
+        spawn(async move {
+            let configs = handle.state::<ConfigState>().current();
+            if configs.name() != change.profile {
+                return;
+            }
+            match configs.reload_file(&change.config).await {
+                Ok(true) => {
+                    info!("reloaded {} changed on disk", change.config);
+                    if change.config == SETTINGS_CFG {
+                        apply_language(&configs);
+                    }
+                    if let Err(e) = handle.emit(CONFIG_CHANGED_EVENT, &change) {
+                        warn!("Failed to emit config change: {e}");
+                    }
+                }
+                Ok(false) => {}
+                // 外部编辑器可能还没写完，等待下一次变更
+                Err(e) => warn!("reload {} failed: {e:?}", change.config),
+            }
+        });
+    })
+    .context("watch config dir")?;
+    Ok(ConfigWatcher {
+        _watcher: Mutex::new(watcher),
+    })
+}
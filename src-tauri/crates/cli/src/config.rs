@@ -0,0 +1,53 @@
+use anyhow::{Context, anyhow};
+use clap::Subcommand;
+use maa_cfg::{Config, ConfigType, Parameters};
+use serde_json::Value;
+
+#[derive(Subcommand)]
+pub enum Action {
+    /// print configs as json
+    Get {
+        /// config name like `daily` or `settings`, prints all if omitted
+        config: Option<String>,
+        /// entry in the config like `Fight` or `Adb`
+        key: Option<String>,
+    },
+    /// update an entry, same as the `update_config` command of the app
+    Set {
+        /// task type, setting name or custom task like `Fight`, `Adb` or
+        /// `custom:foo`
+        name: String,
+        /// parameters in json
+        params: String,
+    },
+}
+
+pub async fn config(profile: String, action: Action) -> anyhow::Result<()> {
+    let config = Config::load(Some(profile.clone()))
+        .await
+        .with_context(|| format!("load profile {profile}"))?;
+    match action {
+        Action::Get { config: cfg, key } => {
+            let mut value = serde_json::to_value(&config).context("serde config")?;
+            for k in cfg.iter().chain(key.iter()) {
+                value = value
+                    .get_mut(k)
+                    .map(Value::take)
+                    .with_context(|| format!("{k} not found"))?;
+            }
+            println!(
+                "{}",
+                serde_json::to_string_pretty(&value).context("serde config")?
+            );
+            Ok(())
+        }
+        Action::Set { name, params } => {
+            let cfg_type: ConfigType = name.parse().map_err(|e| anyhow!("unknown config {e}"))?;
+            let params: Parameters = serde_json::from_str(&params).context("parse params")?;
+            config
+                .set_and_write(cfg_type, params)
+                .await
+                .context("update config")
+        }
+    }
+}
@@ -0,0 +1,84 @@
+use std::{env::current_dir, path::Path};
+
+use anyhow::{Context, bail};
+use maa_cfg::{Config, QueueKind};
+use maa_core::{
+    MAA_CORE,
+    backend::{CoreBackend, MaaSysBackend},
+};
+use maa_updater::version::Versions;
+
+/// 逐项检查运行环境，有失败项时返回错误
+pub async fn doctor(profile: String) -> anyhow::Result<()> {
+    let mut failed = 0;
+    let mut check = |name: &str, result: anyhow::Result<String>| match result {
+        Ok(detail) => println!("[ok]   {name}: {detail}"),
+        Err(e) => {
+            failed += 1;
+            println!("[fail] {name}: {e:#}");
+        }
+    };
+
+    let cwd = current_dir().context("cwd")?;
+    check("MaaCore", check_core(&cwd));
+    check("versions", check_versions());
+
+    match Config::load(Some(profile.clone())).await {
+        Ok(config) => {
+            check("profile", Ok(profile));
+            check("adb", check_adb(&config));
+            check("daily queue", check_queue(&config));
+            check(
+                "schedule",
+                config
+                    .schedule_config()
+                    .and_then(|s| s.validate().map(|_| s))
+                    .map(|s| display_enabled(s.enable)),
+            );
+        }
+        Err(e) => check("profile", Err(e.context(format!("load profile {profile}")))),
+    }
+
+    if failed > 0 {
+        bail!("{failed} check(s) failed");
+    }
+    Ok(())
+}
+
+fn check_core(cwd: &Path) -> anyhow::Result<String> {
+    let path = cwd.join(MAA_CORE);
+    if !path.exists() {
+        bail!("{} not found", path.display());
+    }
+    MaaSysBackend.load_core()?;
+    Ok(path.display().to_string())
+}
+
+fn check_versions() -> anyhow::Result<String> {
+    let versions = Versions::load().context("load versions")?;
+    if !versions.resource.exists() {
+        bail!("resource not found, run `update resource` first");
+    }
+    Ok(format!(
+        "core {}, resource {}",
+        versions.client.version().unwrap_or("unknown"),
+        versions.resource.last_updated
+    ))
+}
+
+fn check_adb(config: &Config) -> anyhow::Result<String> {
+    let adb = config.adb_config().context("get adb config")?;
+    if adb.path.components().count() > 1 && !adb.path.exists() {
+        bail!("{} not found", adb.path.display());
+    }
+    Ok(format!("{} {}", adb.path.display(), adb.address))
+}
+
+fn check_queue(config: &Config) -> anyhow::Result<String> {
+    let tasks = config.task_queue(&QueueKind::Daily)?;
+    Ok(format!("{} enabled task(s)", tasks.len()))
+}
+
+fn display_enabled(enable: bool) -> String {
+    if enable { "enabled" } else { "disabled" }.to_string()
+}
@@ -0,0 +1,98 @@
+#![deny(warnings)]
+
+mod config;
+mod doctor;
+mod recruit;
+mod run;
+mod update;
+
+use std::env::{current_exe, set_current_dir};
+
+use anyhow::Context;
+use clap::{Parser, Subcommand};
+use log::LevelFilter;
+use maa_cfg::profile::active_profile;
+use maa_core::logger::console_log_config;
+use maa_updater::version::ClientVersionRequest;
+
+/// 与桌面端共用同一目录下的配置、资源和日志
+#[derive(Parser)]
+#[command(name = "maa-se-cli", version, about)]
+struct Cli {
+    /// log level of the log file
+    #[arg(long, global = true, default_value = "trace")]
+    log_level: LevelFilter,
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// run a task queue of a profile
+    Run {
+        /// profile to run, defaults to the active profile
+        #[arg(long)]
+        profile: Option<String>,
+        /// `daily`, `extra`, `customs` or the name of a custom queue
+        #[arg(long, default_value = "daily")]
+        queue: String,
+    },
+    /// update MaaCore or resources
+    Update {
+        #[arg(value_enum)]
+        target: update::Target,
+        /// release channel of MaaCore: `stable`, `beta` or `nightly`
+        #[arg(long, default_value = "stable")]
+        channel: ClientVersionRequest,
+    },
+    /// read or write configs of a profile
+    Config {
+        /// profile to use, defaults to the active profile
+        #[arg(long, global = true)]
+        profile: Option<String>,
+        #[command(subcommand)]
+        action: config::Action,
+    },
+    /// calculate recruitment results offline with local resources
+    Recruit {
+        /// up to 5 tags shown in the recruitment slot
+        #[arg(required = true, num_args = 1..=5)]
+        tags: Vec<String>,
+    },
+    /// check the environment and configs
+    Doctor {
+        /// profile to check, defaults to the active profile
+        #[arg(long)]
+        profile: Option<String>,
+    },
+}
+
+#[tokio::main(flavor = "multi_thread")]
+async fn main() -> anyhow::Result<()> {
+    let cli = Cli::parse();
+    init_cwd()?;
+    let config = console_log_config(cli.log_level).context("get log config")?;
+    log4rs::init_config(config).context("init log with config")?;
+
+    match cli.command {
+        Command::Run { profile, queue } => run::run(or_active(profile).await?, &queue).await,
+        Command::Update { target, channel } => update::update(target, channel).await,
+        Command::Config { profile, action } => {
+            config::config(or_active(profile).await?, action).await
+        }
+        Command::Recruit { tags } => recruit::recruit(tags),
+        Command::Doctor { profile } => doctor::doctor(or_active(profile).await?).await,
+    }
+}
+
+async fn or_active(profile: Option<String>) -> anyhow::Result<String> {
+    match profile {
+        Some(p) => Ok(p),
+        None => active_profile().await.context("get active profile"),
+    }
+}
+
+fn init_cwd() -> anyhow::Result<()> {
+    let exe = current_exe().context("get exe path")?;
+    set_current_dir(exe.parent().unwrap()).context("set cwd")
+}
@@ -0,0 +1,19 @@
+use maa_core::tools::recruit_calc::Recruitment;
+
+/// 输出每个标签组合的保底星级和可能的干员
+pub fn recruit(tags: Vec<String>) -> anyhow::Result<()> {
+    let results = Recruitment::load()?.calculate(&tags)?;
+    if results.is_empty() {
+        println!("no operator matches these tags");
+    }
+    for entry in results {
+        let opers = entry
+            .opers
+            .iter()
+            .map(|oper| format!("{}({}★)", oper.name, oper.level))
+            .collect::<Vec<_>>()
+            .join(" ");
+        println!("{}★ [{}] {opers}", entry.level, entry.tags.join(" "));
+    }
+    Ok(())
+}
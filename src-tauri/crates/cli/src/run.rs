@@ -0,0 +1,70 @@
+use std::sync::Arc;
+
+use anyhow::{Context, ensure};
+use log::{info, warn};
+use maa_callback::{
+    callback::{self, CallbackStats, default_callback_log},
+    i18n,
+};
+use maa_cfg::{Config, QueueKind};
+use maa_core::{registry, tools::ToolResult};
+use tokio::{runtime::Handle, select, signal::ctrl_c, task::spawn_blocking};
+
+/// 以配置组名作为实例 id 运行，Ctrl-C 时停止
+pub async fn run(profile: String, queue: &str) -> anyhow::Result<()> {
+    let kind: QueueKind = queue.parse().unwrap();
+    let config = Config::load(Some(profile.clone()))
+        .await
+        .with_context(|| format!("load profile {profile}"))?;
+    let tasks = config.task_queue(&kind)?;
+    ensure!(!tasks.is_empty(), "no enabled task in {kind}");
+    let adb_cfg = config.adb_config().context("get adb config")?;
+    apply_language(&config)?;
+    init_tool_storage(Arc::new(config));
+
+    info!("run {kind} of {profile}");
+    let id = profile.clone();
+    let mut run =
+        spawn_blocking(move || maa_core::run_core(&id, tasks, Some(default_callback_log), adb_cfg));
+    let result = select! {
+        result = &mut run => result,
+        _ = ctrl_c() => {
+            warn!("interrupted, stopping {profile}");
+            if let Err(e) = registry::stop(&profile) {
+                warn!("{e}");
+            }
+            run.await
+        }
+    };
+    // 运行结束前已等待回调处理完，识别结果已经保存
+    let stats = callback::stats();
+    if stats != CallbackStats::default() {
+        warn!(
+            "{} callback messages dropped, {} unparsable",
+            stats.dropped, stats.unparsable
+        );
+    }
+    result
+        .context("join run")?
+        .with_context(|| format!("run {kind} of {profile}"))
+}
+
+/// 回调消息使用配置组设置的语言
+fn apply_language(config: &Config) -> anyhow::Result<()> {
+    i18n::set_language(config.language_config()?.language()?);
+    Ok(())
+}
+
+/// 识别结果在回调线程中同步保存，保证退出前已写入
+fn init_tool_storage(config: Arc<Config>) {
+    let runtime = Handle::current();
+    maa_callback::event::set_listener(move |instance, event| {
+        let saved = ToolResult::from_event(instance, event).and_then(|result| match result {
+            Some(result) => runtime.block_on(result.save(&config)),
+            None => Ok(()),
+        });
+        if let Err(e) = saved {
+            warn!("save tool result: {e:?}");
+        }
+    });
+}
@@ -0,0 +1,54 @@
+use std::{env::current_dir, time::Duration};
+
+use anyhow::Context;
+use clap::ValueEnum;
+use log::info;
+use maa_updater::{
+    download_reporter::DefaultDownloadReporter,
+    updater::{UpdateResult, Updater},
+    version::{ClientVersionRequest, Versions},
+};
+
+const DOWNLOAD_REPORT_INTERVAL: Duration = Duration::from_secs(2);
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum Target {
+    Core,
+    Resource,
+}
+
+/// 资源更新不区分渠道，忽略 `channel`
+pub async fn update(target: Target, channel: ClientVersionRequest) -> anyhow::Result<()> {
+    let updater = Updater::new(DefaultDownloadReporter::new(
+        DOWNLOAD_REPORT_INTERVAL,
+        Some(|downloaded: f64, total: f64| async move {
+            info!("downloaded {:.1}%", downloaded / total * 100.0);
+        }),
+    ));
+    let dst = current_dir().context("cwd")?;
+    let versions = Versions::load().context("load versions")?;
+    let result = match target {
+        Target::Core => {
+            let result = updater
+                .update(versions.client, channel, &dst)
+                .await
+                .context("update core")?;
+            if let UpdateResult::ClientSuccess(v) = &result {
+                v.write().context("write client version")?;
+            }
+            result
+        }
+        Target::Resource => updater
+            .update_resource(versions.resource, &dst)
+            .await
+            .context("update resource")?,
+    };
+    match result {
+        UpdateResult::ClientSuccess(v) => {
+            info!("MaaCore updated to {}", v.version().unwrap_or("unknown"))
+        }
+        UpdateResult::ResourceSuccess(v) => info!("resource updated to {}", v.last_updated),
+        res => info!("{res}"),
+    }
+    Ok(())
+}
@@ -1,51 +1,238 @@
 use std::{
     ffi::{CStr, c_char, c_void},
-    sync::LazyLock,
+    panic::{self, AssertUnwindSafe},
+    sync::{
+        Arc, LazyLock,
+        atomic::{AtomicU64, Ordering},
+    },
+    thread,
+    time::Duration,
 };
 
 use crossbeam_channel::{Receiver, Sender, bounded};
 use log::Level;
 use maa_types::primitive::AsstMsgId;
+use serde::Serialize;
 use strum::{Display, FromRepr};
 
 use crate::{
     callback_types::{ConnectionInfo, ConnectionInfoType},
+    event::{self, MaaEvent},
     msg_handler,
 };
 
-pub struct StopChan {
-    pub tx: Sender<()>,
-    pub rx: Receiver<()>,
+type TaskHook = Box<dyn Fn(&RunContext, &str) + Send + Sync>;
+
+/// 每次运行的回调上下文，通过 `arg` 指针传给回调
+pub struct RunContext {
+    /// 实例 id
+    pub id: String,
+    stop_tx: Sender<()>,
+    stop_rx: Receiver<()>,
+    /// 任务链开始时在回调线程中调用，不能 panic
+    ///
+    /// 参数为本次运行的上下文和任务链名，如 `Fight`
+    on_task: Option<TaskHook>,
 }
 
-pub static STOP_CHAN: LazyLock<StopChan> = LazyLock::new(|| {
-    let (tx, rx) = bounded(0);
-    StopChan { tx, rx }
+impl RunContext {
+    pub fn new(id: impl Into<String>) -> Self {
+        let (stop_tx, stop_rx) = bounded(1);
+        Self {
+            id: id.into(),
+            stop_tx,
+            stop_rx,
+            on_task: None,
+        }
+    }
+
+    pub fn with_task_hook(
+        mut self,
+        hook: impl Fn(&RunContext, &str) + Send + Sync + 'static,
+    ) -> Self {
+        self.on_task = Some(Box::new(hook));
+        self
+    }
+
+    fn task_started(&self, json_str: &str) {
+        let Some(hook) = &self.on_task else {
+            return;
+        };
+        let value = serde_json::from_str::<serde_json::Value>(json_str).unwrap_or_default();
+        match value["taskchain"].as_str() {
+            Some(task) => hook(self, task),
+            None => log::warn!("no taskchain in {json_str}"),
+        }
+    }
+
+    /// 请求停止，不会阻塞，重复的请求会被合并
+    pub fn stop(&self) {
+        let _ = self.stop_tx.try_send(());
+    }
+
+    pub fn stop_rx(&self) -> &Receiver<()> {
+        &self.stop_rx
+    }
+
+    /// 作为回调的 `arg`，调用方需要保证 `Assistant` 销毁前上下文不会被释放
+    pub fn as_arg(self: &Arc<Self>) -> *mut c_void {
+        Arc::as_ptr(self).cast_mut().cast()
+    }
+
+    /// 取得上下文的一个引用计数，使其在回调处理完之前不会被释放
+    ///
+    /// # Safety
+    ///
+    /// `arg` 必须为空或来自 [`RunContext::as_arg`]，且上下文仍然存活
+    pub unsafe fn from_arg(arg: *mut c_void) -> Option<Arc<Self>> {
+        let ptr = arg.cast_const().cast::<Self>();
+        if ptr.is_null() {
+            return None;
+        }
+        unsafe {
+            Arc::increment_strong_count(ptr);
+            Some(Arc::from_raw(ptr))
+        }
+    }
+}
+
+/// 回调队列的容量，队列已满时新的消息会被丢弃
+pub const CALLBACK_QUEUE_CAPACITY: usize = 1024;
+
+/// 从 MaaCore 回调中复制出的消息
+struct CallbackMessage {
+    code: AsstMsgId,
+    json: Vec<u8>,
+    ctx: Option<Arc<RunContext>>,
+}
+
+enum Job {
+    Message(CallbackMessage),
+    /// 处理完之前的消息后回复
+    Flush(Sender<()>),
+}
+
+/// 工作线程创建失败时接收端随之释放
+///
+/// 之后的消息都计入丢弃，`flush` 立即返回 false
+static QUEUE: LazyLock<Sender<Job>> = LazyLock::new(|| {
+    let (tx, rx) = bounded(CALLBACK_QUEUE_CAPACITY);
+    let spawned = thread::Builder::new()
+        .name("maa-callback".to_string())
+        .spawn(move || rx.iter().for_each(handle_job));
+    if let Err(e) = spawned {
+        log::error!("spawn callback worker failed: {e}");
+    }
+    tx
 });
 
+static DROPPED: AtomicU64 = AtomicU64::new(0);
+static UNPARSABLE: AtomicU64 = AtomicU64::new(0);
+
+/// 启动以来丢弃和无法解析的回调消息数
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct CallbackStats {
+    /// 队列已满时丢弃的消息
+    pub dropped: u64,
+    /// 无法解析或处理时 panic 的消息
+    pub unparsable: u64,
+}
+
+pub fn stats() -> CallbackStats {
+    CallbackStats {
+        dropped: DROPPED.load(Ordering::Relaxed),
+        unparsable: UNPARSABLE.load(Ordering::Relaxed),
+    }
+}
+
+/// 等待已收到的消息处理完，超时返回 false
+pub fn flush(timeout: Duration) -> bool {
+    let (tx, rx) = bounded(1);
+    QUEUE.send_timeout(Job::Flush(tx), timeout).is_ok() && rx.recv_timeout(timeout).is_ok()
+}
+
 /// default callback function
 ///
 /// # Safety
 ///
 /// This function is unsafe because it passes C pointer from DLL.
 ///
+/// Only copies the message into the callback queue and returns, the message
+/// is parsed and handled on the worker thread.
+///
 /// # Parameters
 ///
 /// - `code`: message code, see `AsstMsgCode`
 /// - `json_raw`: message details in JSON c_str pointer
+/// - `arg`: `RunContext` of this run, see `RunContext::as_arg`
 pub unsafe extern "C" fn default_callback_log(
     code: AsstMsgId,
     json_raw: *const c_char,
-    _: *mut c_void,
+    arg: *mut c_void,
 ) {
+    let ctx = unsafe { RunContext::from_arg(arg) };
+    // 停止请求不能因为队列已满而丢失
+    if let Some(ctx) = &ctx
+        && matches!(
+            AsstMsgCode::from_repr(code),
+            Some(AsstMsgCode::AllTasksCompleted)
+        )
+    {
+        ctx.stop();
+    }
+
+    let json = if json_raw.is_null() {
+        vec![]
+    } else {
+        unsafe { CStr::from_ptr(json_raw) }.to_bytes().to_vec()
+    };
+    let message = CallbackMessage { code, json, ctx };
+    if QUEUE.try_send(Job::Message(message)).is_err() {
+        DROPPED.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+/// release 构建使用 `panic = "abort"`，`catch_unwind` 只在 debug 构建中兜底
+///
+/// 因此消息处理和监听者中不能有会 panic 的路径：
+/// 解析失败一律返回错误或保留原文，多语言文本缺失时回退到 id，
+/// 任务钩子和监听者只记录错误
+fn handle_job(job: Job) {
+    match job {
+        Job::Message(message) => {
+            let code = message.code;
+            let handled = panic::catch_unwind(AssertUnwindSafe(|| handle_message(message)));
+            let parsed = handled.unwrap_or_else(|_| {
+                log::error!("handle callback {code} panicked");
+                false
+            });
+            if !parsed {
+                UNPARSABLE.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+        Job::Flush(done) => {
+            let _ = done.send(());
+        }
+    }
+}
+
+/// 解析并分发一条消息，无法解析时返回 false
+fn handle_message(message: CallbackMessage) -> bool {
     use std::str::FromStr;
 
-    let json_str = unsafe { CStr::from_ptr(json_raw).to_str().unwrap() };
-    let msg_type = AsstMsgCode::from_repr(code).unwrap_or_default();
+    let msg_type = AsstMsgCode::from_repr(message.code).unwrap_or_default();
+    let json_str = match std::str::from_utf8(&message.json) {
+        Ok(s) => s,
+        Err(e) => {
+            log::error!("[{msg_type}] invalid utf-8: {e}");
+            return false;
+        }
+    };
 
     let level = if matches!(msg_type, AsstMsgCode::ConnectionInfo) {
-        let info: ConnectionInfo = serde_json::from_str(json_str).unwrap();
-        ConnectionInfoType::from_str(&info.what)
+        serde_json::from_str::<ConnectionInfo>(json_str)
+            .ok()
+            .and_then(|info| ConnectionInfoType::from_str(&info.what).ok())
             .unwrap_or(ConnectionInfoType::Others)
             .level()
     } else {
@@ -60,16 +247,27 @@ pub unsafe extern "C" fn default_callback_log(
         Level::Trace => log::trace!("[{}] {}", msg_type, json_str),
     }
 
-    if matches!(msg_type, AsstMsgCode::AllTasksCompleted)
-        && let Err(e) = STOP_CHAN.tx.send(())
+    let ctx = message.ctx.as_deref();
+    if let Some(ctx) = ctx
+        && matches!(msg_type, AsstMsgCode::TaskChainStart)
     {
-        log::error!("{e}");
+        ctx.task_started(json_str);
     }
 
+    let instance = ctx.map(|c| c.id.as_str()).unwrap_or_default();
+    let event = match MaaEvent::parse(msg_type, json_str) {
+        Ok(event) => event,
+        Err(e) => {
+            log::error!("[{}] {:#}", msg_type, e);
+            return false;
+        }
+    };
+    event::emit(instance, &event);
     // 简化log
-    if let Err(e) = msg_handler::notify(msg_type, json_str) {
+    if let Err(e) = msg_handler::notify(instance, event) {
         log::error!("[{}] {}", msg_type, e)
     }
+    true
 }
 
 #[derive(Default, Debug, Display, Clone, Copy, FromRepr)]
@@ -152,3 +350,42 @@ impl AsstMsgCode {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::ptr;
+
+    use super::*;
+
+    #[test]
+    fn survive_malformed_messages() {
+        let ctx = Arc::new(RunContext::new("callback-test"));
+        let before = stats();
+        let messages: [&[u8]; 3] = [b"\xff\xfe\0", b"{\"what\": 1}\0", b"not json\0"];
+        unsafe {
+            default_callback_log(
+                AsstMsgCode::ConnectionInfo as i32,
+                ptr::null(),
+                ctx.as_arg(),
+            );
+            for msg in messages {
+                let msg = CStr::from_bytes_with_nul(msg).unwrap();
+                default_callback_log(
+                    AsstMsgCode::ConnectionInfo as i32,
+                    msg.as_ptr(),
+                    ctx.as_arg(),
+                );
+            }
+            default_callback_log(
+                AsstMsgCode::AllTasksCompleted as i32,
+                c"{}".as_ptr(),
+                ctx.as_arg(),
+            );
+        }
+        assert!(ctx.stop_rx().try_recv().is_ok());
+        assert!(flush(Duration::from_secs(5)));
+        assert!(stats().unparsable >= before.unparsable + 4);
+        // 处理完后队列不再持有上下文
+        assert_eq!(Arc::strong_count(&ctx), 1);
+    }
+}
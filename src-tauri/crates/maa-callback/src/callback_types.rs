@@ -1,15 +1,18 @@
-use anyhow::Context;
 pub use depot_types::*;
 pub use facility_types::*;
 use log::Level;
 pub use oper_box_types::*;
+pub use reclamation_types::*;
 pub use recruit_types::*;
-use serde::Deserialize;
+pub use roguelike_types::*;
+use serde::{Deserialize, Serialize};
 use serde_json::Value;
 pub use stage_types::*;
-use strum::{AsRefStr, Display, EnumString};
+use strum::{AsRefStr, Display, EnumString, IntoStaticStr};
 
-const PROCESS_TASK_NAME: &str = "ProcessTask";
+use crate::i18n;
+
+pub(crate) const PROCESS_TASK_NAME: &str = "ProcessTask";
 
 #[derive(Debug, Display, EnumString)]
 pub enum ConnectionInfoType {
@@ -58,7 +61,7 @@ impl ConnectionInfoType {
     }
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct ConnectionInfo {
     pub what: String,         // 信息类型
     pub why: Option<String>,  // 信息原因
@@ -73,48 +76,40 @@ pub struct TaskChainInfo {
 
 impl TaskChainInfo {
     pub fn get_task_chain_name(&self) -> &str {
-        self.taskchain.as_ref()
+        self.taskchain.name()
     }
 }
 
-#[derive(Debug, Deserialize, AsRefStr)]
+#[derive(Debug, Deserialize, AsRefStr, IntoStaticStr)]
 pub enum TaskChainType {
-    #[strum(serialize = "开始唤醒")]
     StartUp,
-    #[strum(serialize = "关闭游戏")]
     CloseDown,
-    #[strum(serialize = "刷理智")]
     Fight,
-    #[strum(serialize = "信用点及购物")]
     Mall,
-    #[strum(serialize = "自动公招")]
     Recruit,
-    #[strum(serialize = "基建换班")]
     Infrast,
-    #[strum(serialize = "领取日常奖励")]
     Award,
-    #[strum(serialize = "无限刷肉鸽")]
     Roguelike,
-    #[strum(serialize = "自动抄作业")]
     Copilot,
-    #[strum(serialize = "自动抄保全作业")]
     SSSCopilot,
-    #[strum(serialize = "仓库识别")]
     Depot,
-    #[strum(serialize = "干员 box 识别")]
     OperBox,
-    #[strum(serialize = "生息演算")]
     ReclamationAlgorithm,
-    #[strum(serialize = "自定义任务")]
     Custom,
-    #[strum(serialize = "单步任务")]
     SingleStep,
-    #[strum(serialize = "视频识别任务")]
     VideoRecognition,
-    #[strum(serialize = "调试")]
     Debug,
 }
 
+impl TaskChainType {
+    /// 当前语言下的任务链名
+    pub fn name(&self) -> &'static str {
+        i18n::language()
+            .get(&format!("taskchain.{}", self.as_ref()))
+            .unwrap_or(self.into())
+    }
+}
+
 #[derive(Debug, Deserialize)]
 pub struct SubTask {
     subtask: String, // 子任务名
@@ -129,83 +124,29 @@ impl SubTask {
         self.details["task"]
             .as_str()
             .ok_or(anyhow::anyhow!("process task not exists"))
-            .map(Self::as_task_type_cn)
-    }
-
-    pub fn as_task_type_cn(task_type: &str) -> Option<&'static str> {
-        match task_type {
-            "StartButton2" => Some("开始战斗"),
-            "MedicineConfirm" => Some("使用理智药"),
-            "ExpiringMedicineConfirm" => Some("使用 48 小时内过期的理智药"),
-            "StoneConfirm" => Some("碎石"),
-            "RecruitRefreshConfirm" => Some("公招刷新标签"),
-            "RecruitConfirm" => Some("公招确认招募"),
-            "RecruitNowConfirm" => Some("公招使用加急许可"),
-            "ReportToPenguinStats" => Some("汇报到企鹅数据统计"),
-            "ReportToYituliu" => Some("汇报到一图流大数据"),
-            "InfrastDormDoubleConfirmButton" => Some("宿舍二次确认"),
-            "StartExplore" => Some("肉鸽开始探索"),
-            "StageTraderInvestConfirm" => Some("肉鸽投资了源石锭"),
-            "StageTraderInvestSystemFull" => Some("肉鸽投资达到了游戏上限"),
-            "ExitThenAbandon" => Some("肉鸽放弃了本次探索"),
-            "MissionCompletedFlag" => Some("肉鸽战斗完成"),
-            "MissionFailedFlag" => Some("肉鸽战斗失败"),
-            "StageTraderEnter" => Some("肉鸽关卡：诡异行商"),
-            "StageSafeHouseEnter" => Some("肉鸽关卡：安全的角落"),
-            "StageEncounterEnter" => Some("肉鸽关卡：不期而遇/古堡馈赠"),
-            "StageCombatDpsEnter" => Some("肉鸽关卡：普通作战"),
-            "StageEmergencyDps" => Some("肉鸽关卡：紧急作战"),
-            "StageDreadfulFoe" => Some("肉鸽关卡：险路恶敌"),
-            "StartGameTask" => Some("打开客户端"),
-            _ => None,
-        }
+            .map(Self::task_type_name)
     }
-}
 
-#[derive(Deserialize, Debug)]
-pub struct SubTaskExtraInfo {
-    what: String,   // 信息类型
-    details: Value, // 信息详情
+    /// 当前语言下值得展示的步骤名，其余步骤为空
+    pub fn task_type_name(task_type: &str) -> Option<&'static str> {
+        i18n::language().get(&format!("subtask.{task_type}"))
+    }
 }
 
 pub trait ExtraInfoDisplay {
     fn into_info(self) -> anyhow::Result<String>;
 }
 
-impl SubTaskExtraInfo {
-    pub fn to_exact_info(self) -> anyhow::Result<Option<String>> {
-        let info = match self.what.as_str() {
-            "StageDrops" => self.to_drops_info(),
-            "RecruitResult" => self.to_recruit_result_info(),
-            // TODO 其他结果
-            _ => return Ok(None),
-        };
-        info.map(Some)
-    }
-
-    pub fn to_drops_info(self) -> anyhow::Result<String> {
-        let stage_drops: StageDrops =
-            serde_json::from_value(self.details).context("parse stage drops")?;
-        stage_drops.into_info()
-    }
-
-    pub fn to_recruit_result_info(self) -> anyhow::Result<String> {
-        let recuit_result: RecruitResult =
-            serde_json::from_value(self.details).context("parse recruit result")?;
-        recuit_result.into_info()
-    }
-}
-
 /// 关卡掉落相关json
-#[allow(unused)]
 pub mod stage_types {
     use std::fmt::{Debug, Display, Write};
 
-    use serde::Deserialize;
+    use serde::{Deserialize, Serialize};
 
     use super::ExtraInfoDisplay;
+    use crate::tr;
 
-    #[derive(Deserialize, Debug)]
+    #[derive(Serialize, Deserialize, Debug)]
     pub struct StageDrops {
         pub stage: Stage,
         pub stars: u8,
@@ -217,8 +158,12 @@ pub mod stage_types {
             let mut s = String::new();
             writeln!(
                 s,
-                "{}: {}星通过\n材料掉落:",
-                self.stage.stage_code, self.stars
+                "{}",
+                tr!(
+                    "stage_drops",
+                    stage = self.stage.stage_code,
+                    stars = self.stars
+                )
             )?;
 
             self.stats
@@ -229,73 +174,110 @@ pub mod stage_types {
         }
     }
 
-    #[derive(Deserialize, Debug)]
+    #[derive(Serialize, Deserialize, Debug)]
     #[serde(rename_all = "camelCase")]
     pub struct Stage {
         pub stage_code: String,
+        #[serde(default)]
+        pub stage_id: String,
+        /// 理智消耗，消息中没有时为空
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        pub ap_cost: Option<u32>,
     }
 
-    #[derive(Deserialize, Debug)]
+    #[derive(Serialize, Deserialize, Debug)]
     #[serde(rename_all = "camelCase")]
     pub struct Stat {
-        item_name: String,
-        quantity: u32,
-        add_quantity: u32,
+        #[serde(default)]
+        pub item_id: String,
+        pub item_name: String,
+        pub quantity: u32,
+        pub add_quantity: u32,
     }
 
     impl Display for Stat {
         fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
             writeln!(
                 f,
-                "[{}]+{} (总计:{})",
-                self.item_name, self.add_quantity, self.quantity,
+                "{}",
+                tr!(
+                    "stage_drop_item",
+                    item = self.item_name,
+                    add = self.add_quantity,
+                    total = self.quantity
+                )
             )
         }
     }
 
-    #[derive(Deserialize, Debug)]
+    #[derive(Serialize, Deserialize, Debug)]
     pub struct PenguinId {
-        id: String,
+        pub id: String,
+    }
+
+    impl ExtraInfoDisplay for PenguinId {
+        fn into_info(self) -> anyhow::Result<String> {
+            Ok(tr!("penguin_id", id = self.id))
+        }
     }
 
     /// 自动作战关卡的信息
-    #[derive(Deserialize, Debug)]
+    #[derive(Serialize, Deserialize, Debug)]
     pub struct StageInfo {
-        name: String, // 关卡名
+        pub name: String, // 关卡名
+    }
+
+    impl ExtraInfoDisplay for StageInfo {
+        fn into_info(self) -> anyhow::Result<String> {
+            Ok(tr!("stage_info", name = self.name))
+        }
     }
 }
 
 /// 公招相关json
-#[allow(unused)] //TODO: 公招识别模块相关暂未实现
 pub mod recruit_types {
-    use serde::Deserialize;
+    use serde::{Deserialize, Serialize};
 
     use super::ExtraInfoDisplay;
+    use crate::{i18n, tr};
 
-    #[derive(Deserialize, Debug)]
+    #[derive(Serialize, Deserialize, Debug)]
     pub struct RecruitTagsDetected {
-        tags: Vec<String>,
+        pub tags: Vec<String>,
     }
 
-    #[derive(Deserialize, Debug)]
+    impl ExtraInfoDisplay for RecruitTagsDetected {
+        fn into_info(self) -> anyhow::Result<String> {
+            Ok(tr!("recruit_tags_detected", tags = self.tags.join(" ")))
+        }
+    }
+
+    /// 高级资深干员、支援机械等需要提醒的标签
+    #[derive(Serialize, Deserialize, Debug)]
     pub struct RecruitSpecialTag {
-        tag: String,
+        pub tag: String,
     }
 
-    #[derive(Deserialize, Debug)]
+    impl ExtraInfoDisplay for RecruitSpecialTag {
+        fn into_info(self) -> anyhow::Result<String> {
+            Ok(tr!("recruit_special_tag", tag = self.tag))
+        }
+    }
+
+    #[derive(Serialize, Deserialize, Debug)]
     pub struct Oper {
-        name: String,
-        level: u32,
+        pub name: String,
+        pub level: u32,
     }
 
-    #[derive(Deserialize, Debug)]
+    #[derive(Serialize, Deserialize, Debug)]
     pub struct ResultEntry {
-        tags: Vec<String>,
-        level: u8,
-        opers: Vec<Oper>,
+        pub tags: Vec<String>,
+        pub level: u8,
+        pub opers: Vec<Oper>,
     }
 
-    #[derive(Deserialize, Debug)]
+    #[derive(Serialize, Deserialize, Debug)]
     pub struct RecruitResult {
         pub tags: Vec<String>,
         pub level: u8,
@@ -304,132 +286,380 @@ pub mod recruit_types {
 
     impl ExtraInfoDisplay for RecruitResult {
         fn into_info(self) -> anyhow::Result<String> {
-            Ok(format!("公招标签 ({}星):{:#?}", self.level, self.tags))
+            Ok(tr!(
+                "recruit_result",
+                level = self.level,
+                tags = format!("{:#?}", self.tags)
+            ))
         }
     }
 
-    #[derive(Deserialize, Debug)]
+    #[derive(Serialize, Deserialize, Debug)]
     pub struct RecruitTagsRefreshed {
-        count: u8,
-        refresh_limit: u8,
+        pub count: u8,
+        pub refresh_limit: u8,
+    }
+
+    impl ExtraInfoDisplay for RecruitTagsRefreshed {
+        fn into_info(self) -> anyhow::Result<String> {
+            Ok(tr!(
+                "recruit_tags_refreshed",
+                count = self.count,
+                limit = self.refresh_limit
+            ))
+        }
     }
 
-    #[derive(Deserialize, Debug)]
+    #[derive(Serialize, Deserialize, Debug)]
     pub struct RecruitNoPermit {
         #[serde(rename = "continue")]
-        continue_: bool,
+        pub continue_: bool,
     }
 
-    #[derive(Deserialize, Debug)]
+    impl ExtraInfoDisplay for RecruitNoPermit {
+        fn into_info(self) -> anyhow::Result<String> {
+            let id = if self.continue_ {
+                "recruit_no_permit_continue"
+            } else {
+                "recruit_no_permit_stop"
+            };
+            Ok(i18n::tr(id).to_string())
+        }
+    }
+
+    #[derive(Serialize, Deserialize, Debug)]
     pub struct RecruitTagsSelected {
-        tags: Vec<String>,
+        pub tags: Vec<String>,
     }
 
     impl ExtraInfoDisplay for RecruitTagsSelected {
         fn into_info(self) -> anyhow::Result<String> {
-            Ok(format!("选择tag：{:#?}", self.tags))
+            Ok(tr!(
+                "recruit_tags_selected",
+                tags = format!("{:#?}", self.tags)
+            ))
         }
     }
 }
 
 /// 基建相关json
-#[allow(unused)]
 pub mod facility_types {
-    use serde::Deserialize;
+    use serde::{Deserialize, Serialize};
 
-    #[derive(Deserialize, Debug)]
+    use super::ExtraInfoDisplay;
+    use crate::{i18n, tr};
+
+    #[derive(Serialize, Deserialize, Debug)]
     pub struct EnterFacility {
-        facility: String, // 设施名
-        index: u16,       // 设施序号
+        pub facility: String, // 设施名
+        pub index: u16,       // 设施序号
+    }
+
+    impl ExtraInfoDisplay for EnterFacility {
+        fn into_info(self) -> anyhow::Result<String> {
+            Ok(tr!(
+                "enter_facility",
+                facility = facility_name(&self.facility),
+                index = self.index
+            ))
+        }
     }
 
-    #[derive(Deserialize, Debug)]
+    #[derive(Serialize, Deserialize, Debug)]
     pub struct NotEnoughStaff {
-        facility: String, // 设施名
-        index: u16,       // 设施序号
+        pub facility: String, // 设施名
+        pub index: u16,       // 设施序号
+    }
+
+    impl ExtraInfoDisplay for NotEnoughStaff {
+        fn into_info(self) -> anyhow::Result<String> {
+            Ok(tr!(
+                "not_enough_staff",
+                facility = facility_name(&self.facility),
+                index = self.index
+            ))
+        }
     }
 
-    #[derive(Deserialize, Debug)]
+    #[derive(Serialize, Deserialize, Debug)]
     pub struct ProductOfFacility {
-        product: String,  // 产物名
-        facility: String, // 设施名
-        index: u8,        // 设施序号
+        pub product: String,  // 产物名
+        pub facility: String, // 设施名
+        pub index: u8,        // 设施序号
+    }
+
+    impl ExtraInfoDisplay for ProductOfFacility {
+        fn into_info(self) -> anyhow::Result<String> {
+            Ok(tr!(
+                "product_of_facility",
+                facility = facility_name(&self.facility),
+                index = self.index,
+                product = self.product
+            ))
+        }
+    }
+
+    /// 当前语言下的设施名，未知的设施返回原名
+    pub fn facility_name(facility: &str) -> &str {
+        i18n::language()
+            .get(&format!("facility.{facility}"))
+            .unwrap_or(facility)
     }
 }
 
 /// 仓库识别相关json
-#[allow(unused)]
 pub mod depot_types {
-    use serde::Deserialize;
+    use std::collections::HashMap;
+
+    use serde::{Deserialize, Serialize};
 
-    #[derive(Deserialize, Debug)]
+    use super::ExtraInfoDisplay;
+    use crate::tr;
+
+    #[derive(Serialize, Deserialize, Debug)]
     pub struct Depot {
-        done: bool,
-        arkplanner: ArkPlanner,
-        lolicon: Lolicon,
+        pub done: bool,
+        pub arkplanner: ArkPlanner,
+        pub lolicon: Lolicon,
+    }
+
+    impl ExtraInfoDisplay for Depot {
+        fn into_info(self) -> anyhow::Result<String> {
+            let count = self.arkplanner.object.items.len();
+            Ok(if self.done {
+                tr!("depot_done", count = count)
+            } else {
+                tr!("depot_progress", count = count)
+            })
+        }
     }
 
-    #[derive(Deserialize, Debug)]
+    #[derive(Serialize, Deserialize, Debug)]
     pub struct Item {
-        id: String,
-        have: u32,
-        name: String,
+        pub id: String,
+        pub have: u32,
+        pub name: String,
     }
 
-    #[derive(Deserialize, Debug)]
+    #[derive(Serialize, Deserialize, Debug)]
     pub struct ArkPlannerObject {
-        items: Vec<Item>,
+        pub items: Vec<Item>,
         #[serde(rename = "@type")]
-        type_: String,
+        pub type_: String,
     }
 
-    #[derive(Deserialize, Debug)]
+    /// `data` 为 `object` 序列化后的字符串
+    #[derive(Serialize, Deserialize, Debug)]
     pub struct ArkPlanner {
-        object: ArkPlannerObject,
-        data: String,
+        pub object: ArkPlannerObject,
+        pub data: String,
     }
 
-    #[derive(Deserialize, Debug)]
+    #[derive(Serialize, Deserialize, Debug)]
     pub struct LoliconObject {
         #[serde(flatten)]
-        items: std::collections::HashMap<String, u32>,
+        pub items: HashMap<String, u32>,
     }
 
-    #[derive(Deserialize, Debug)]
+    #[derive(Serialize, Deserialize, Debug)]
     pub struct Lolicon {
-        object: LoliconObject,
-        data: String,
+        pub object: LoliconObject,
+        pub data: String,
     }
 }
 
 /// 干员识别相关json
-#[allow(unused)]
 pub mod oper_box_types {
-    use serde::Deserialize;
+    use serde::{Deserialize, Serialize};
+
+    use super::ExtraInfoDisplay;
+    use crate::tr;
 
-    #[derive(Deserialize, Debug)]
+    #[derive(Serialize, Deserialize, Debug, Clone)]
     pub struct OperBox {
-        done: bool,
-        all_oper: Vec<OperInfo>,
-        own_opers: Vec<OwnOperInfo>,
+        pub done: bool,
+        pub all_oper: Vec<OperInfo>,
+        pub own_opers: Vec<OwnOperInfo>,
     }
 
-    #[derive(Deserialize, Debug)]
+    impl ExtraInfoDisplay for OperBox {
+        fn into_info(self) -> anyhow::Result<String> {
+            let own = self.own_opers.len();
+            Ok(if self.done {
+                tr!("oper_box_done", own = own, all = self.all_oper.len())
+            } else {
+                tr!("oper_box_progress", own = own)
+            })
+        }
+    }
+
+    #[derive(Serialize, Deserialize, Debug, Clone)]
     pub struct OperInfo {
-        id: String,
-        name: String,
-        own: bool,
-        rarity: u8,
+        pub id: String,
+        pub name: String,
+        pub own: bool,
+        pub rarity: u8,
     }
 
-    #[derive(Deserialize, Debug)]
+    #[derive(Serialize, Deserialize, Debug, Clone)]
     pub struct OwnOperInfo {
-        id: String,
-        name: String,
-        own: bool,
-        elite: u8,
-        level: u8,
-        potential: u8,
-        rarity: u8,
+        pub id: String,
+        pub name: String,
+        pub own: bool,
+        pub elite: u8,
+        pub level: u8,
+        pub potential: u8,
+        pub rarity: u8,
+    }
+}
+
+/// 肉鸽相关json
+pub mod roguelike_types {
+    use serde::{Deserialize, Serialize};
+
+    use super::ExtraInfoDisplay;
+    use crate::{i18n, tr};
+
+    #[derive(Serialize, Deserialize, Debug)]
+    pub struct RoguelikeInvestment {
+        pub count: u32,   // 本次投资数
+        pub total: u32,   // 本轮探索的投资总数
+        pub deposit: u32, // 当前存款
+    }
+
+    impl ExtraInfoDisplay for RoguelikeInvestment {
+        fn into_info(self) -> anyhow::Result<String> {
+            Ok(tr!(
+                "roguelike_investment",
+                count = self.count,
+                total = self.total,
+                deposit = self.deposit
+            ))
+        }
+    }
+
+    /// 一轮探索结束时的结算
+    #[derive(Serialize, Deserialize, Debug, Default)]
+    #[serde(default)]
+    pub struct RoguelikeSettlement {
+        pub game_pass: bool,
+        pub floor: u32,      // 到达的层数
+        pub step: u32,       // 前进的步数
+        pub combat: u32,     // 普通作战
+        pub emergency: u32,  // 紧急作战
+        pub boss: u32,       // 领袖作战
+        pub recruit: u32,    // 招募干员数
+        pub collection: u32, // 收集藏品数
+        pub difficulty: String,
+        pub score: u32,
+        pub exp: u32,
+        pub skill: u32,
+    }
+
+    impl ExtraInfoDisplay for RoguelikeSettlement {
+        fn into_info(self) -> anyhow::Result<String> {
+            let result = i18n::tr(if self.game_pass {
+                "roguelike_pass"
+            } else {
+                "roguelike_fail"
+            });
+            Ok(tr!(
+                "roguelike_settlement",
+                result = result,
+                floor = self.floor,
+                score = self.score,
+                recruit = self.recruit,
+                collection = self.collection
+            ))
+        }
+    }
+
+    #[derive(Serialize, Deserialize, Debug)]
+    pub struct RoguelikeEvent {
+        pub name: String, // 事件名
+    }
+
+    impl ExtraInfoDisplay for RoguelikeEvent {
+        fn into_info(self) -> anyhow::Result<String> {
+            Ok(tr!("roguelike_event", name = self.name))
+        }
+    }
+
+    /// 萨卡兹肉鸽的坍缩范式变化
+    #[derive(Serialize, Deserialize, Debug)]
+    pub struct RoguelikeCollapsalParadigms {
+        pub cur: String,
+        #[serde(default)]
+        pub prev: String,
+        pub deepen_or_weaken: i8, // 1 为加深，-1 为消退
+    }
+
+    impl ExtraInfoDisplay for RoguelikeCollapsalParadigms {
+        fn into_info(self) -> anyhow::Result<String> {
+            Ok(match self.deepen_or_weaken {
+                1 if self.prev.is_empty() => tr!("collapsal_paradigm_deepen", cur = self.cur),
+                1 => tr!(
+                    "collapsal_paradigm_deepen_from",
+                    prev = self.prev,
+                    cur = self.cur
+                ),
+                _ => tr!(
+                    "collapsal_paradigm_weaken",
+                    prev = self.prev,
+                    cur = self.cur
+                ),
+            })
+        }
+    }
+}
+
+/// 生息演算相关json
+pub mod reclamation_types {
+    use serde::{Deserialize, Serialize};
+
+    use super::ExtraInfoDisplay;
+    use crate::tr;
+
+    /// 一局演算结束时的结算
+    #[derive(Serialize, Deserialize, Debug)]
+    pub struct ReclamationReport {
+        pub total_badges: u32,
+        pub badges: u32,
+        pub total_construction_points: u32,
+        pub construction_points: u32,
+    }
+
+    impl ExtraInfoDisplay for ReclamationReport {
+        fn into_info(self) -> anyhow::Result<String> {
+            Ok(tr!(
+                "reclamation_report",
+                badges = self.badges,
+                total_badges = self.total_badges,
+                points = self.construction_points,
+                total_points = self.total_construction_points
+            ))
+        }
+    }
+
+    #[derive(Serialize, Deserialize, Debug)]
+    pub struct ReclamationProcedureStart {
+        pub times: u32,
+    }
+
+    impl ExtraInfoDisplay for ReclamationProcedureStart {
+        fn into_info(self) -> anyhow::Result<String> {
+            Ok(tr!("reclamation_procedure_start", times = self.times))
+        }
+    }
+
+    #[derive(Serialize, Deserialize, Debug)]
+    pub struct ReclamationSmeltGold {
+        pub times: u32,
+    }
+
+    impl ExtraInfoDisplay for ReclamationSmeltGold {
+        fn into_info(self) -> anyhow::Result<String> {
+            Ok(tr!("reclamation_smelt_gold", times = self.times))
+        }
     }
 }
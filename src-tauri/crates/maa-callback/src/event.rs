@@ -0,0 +1,419 @@
+//! 回调消息解析后的事件，序列化后发送给前端
+
+use std::sync::OnceLock;
+
+use anyhow::Context;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::{
+    callback::AsstMsgCode,
+    callback_types::{
+        ConnectionInfo, Depot, EnterFacility, ExtraInfoDisplay, NotEnoughStaff, OperBox,
+        PROCESS_TASK_NAME, PenguinId, ProductOfFacility, ReclamationProcedureStart,
+        ReclamationReport, ReclamationSmeltGold, RecruitNoPermit, RecruitResult, RecruitSpecialTag,
+        RecruitTagsDetected, RecruitTagsRefreshed, RecruitTagsSelected,
+        RoguelikeCollapsalParadigms, RoguelikeEvent, RoguelikeInvestment, RoguelikeSettlement,
+        StageDrops, StageInfo, SubTask, TaskChainType,
+    },
+    i18n,
+};
+
+#[derive(Debug, Serialize)]
+#[serde(tag = "type", content = "details")]
+pub enum MaaEvent {
+    InternalError,
+    InitFailed,
+    ConnectionInfo(ConnectionInfo),
+    AllTasksCompleted(AllTasksCompleted),
+    AsyncCallInfo(Value),
+    Destroyed,
+    TaskChainError(TaskChainEvent),
+    TaskChainStart(TaskChainEvent),
+    TaskChainCompleted(TaskChainEvent),
+    TaskChainExtraInfo(Value),
+    TaskChainStopped(TaskChainEvent),
+    SubTaskError(SubTaskEvent),
+    SubTaskStart(SubTaskEvent),
+    SubTaskCompleted(SubTaskEvent),
+    SubTaskExtraInfo(SubTaskExtraEvent),
+    SubTaskStopped(SubTaskEvent),
+    /// 无法识别的消息码
+    Unknown(Value),
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AllTasksCompleted {
+    /// 最后一个任务链
+    pub taskchain: Option<String>,
+    pub uuid: Option<String>,
+    /// 已完成的任务 id
+    #[serde(default)]
+    pub finished_tasks: Vec<i32>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TaskChainEvent {
+    pub taskchain: String,
+    #[serde(default)]
+    pub taskid: i32,
+    pub uuid: Option<String>,
+}
+
+impl TaskChainEvent {
+    /// 当前语言下的任务链名，未知的任务链返回原名
+    pub fn chain_name(&self) -> &str {
+        serde_json::from_value::<TaskChainType>(Value::String(self.taskchain.clone()))
+            .map(|chain| chain.name())
+            .unwrap_or(self.taskchain.as_str())
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SubTaskEvent {
+    pub subtask: String,
+    pub class: Option<String>,
+    #[serde(default)]
+    pub taskchain: String,
+    #[serde(default)]
+    pub taskid: i32,
+    #[serde(default)]
+    pub details: Value,
+    pub uuid: Option<String>,
+}
+
+impl SubTaskEvent {
+    /// `ProcessTask` 中值得展示的步骤
+    pub fn task_info(&self) -> anyhow::Result<Option<&'static str>> {
+        if self.subtask != PROCESS_TASK_NAME {
+            return Ok(None);
+        }
+        self.details["task"]
+            .as_str()
+            .context("process task not exists")
+            .map(SubTask::task_type_name)
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct SubTaskExtraEvent {
+    pub taskchain: String,
+    pub class: Option<String>,
+    pub taskid: i32,
+    pub uuid: Option<String>,
+    #[serde(flatten)]
+    pub info: ExtraInfo,
+}
+
+/// `SubTaskExtraInfo` 中按 `what` 区分的详情
+#[derive(Debug, Serialize)]
+#[serde(tag = "what", content = "details")]
+pub enum ExtraInfo {
+    StageDrops(StageDrops),
+    StageInfo(StageInfo),
+    StageInfoError,
+    PenguinId(PenguinId),
+    RecruitTagsDetected(RecruitTagsDetected),
+    RecruitSpecialTag(RecruitSpecialTag),
+    RecruitResult(RecruitResult),
+    RecruitTagsRefreshed(RecruitTagsRefreshed),
+    RecruitNoPermit(RecruitNoPermit),
+    RecruitTagsSelected(RecruitTagsSelected),
+    RecruitSlotCompleted,
+    RecruitError,
+    EnterFacility(EnterFacility),
+    NotEnoughStaff(NotEnoughStaff),
+    ProductOfFacility(ProductOfFacility),
+    DepotInfo(Depot),
+    OperBoxInfo(OperBox),
+    RoguelikeInvestment(RoguelikeInvestment),
+    RoguelikeSettlement(RoguelikeSettlement),
+    RoguelikeEvent(RoguelikeEvent),
+    RoguelikeCollapsalParadigms(RoguelikeCollapsalParadigms),
+    ReclamationReport(ReclamationReport),
+    ReclamationProcedureStart(ReclamationProcedureStart),
+    ReclamationSmeltGold(ReclamationSmeltGold),
+    /// 尚未解析的类型，保留原始内容
+    #[serde(untagged)]
+    Other {
+        what: String,
+        details: Value,
+    },
+}
+
+impl ExtraInfo {
+    /// 已知类型的详情格式不符时保留原始内容，视为 `Other`
+    pub fn parse(what: String, details: Value) -> Self {
+        fn de<T: serde::de::DeserializeOwned>(details: &Value) -> serde_json::Result<T> {
+            T::deserialize(details)
+        }
+
+        let info = match what.as_str() {
+            "StageDrops" => de(&details).map(Self::StageDrops),
+            "StageInfo" => de(&details).map(Self::StageInfo),
+            "StageInfoError" => Ok(Self::StageInfoError),
+            "PenguinId" => de(&details).map(Self::PenguinId),
+            "RecruitTagsDetected" => de(&details).map(Self::RecruitTagsDetected),
+            "RecruitSpecialTag" => de(&details).map(Self::RecruitSpecialTag),
+            "RecruitResult" => de(&details).map(Self::RecruitResult),
+            "RecruitTagsRefreshed" => de(&details).map(Self::RecruitTagsRefreshed),
+            "RecruitNoPermit" => de(&details).map(Self::RecruitNoPermit),
+            "RecruitTagsSelected" => de(&details).map(Self::RecruitTagsSelected),
+            "RecruitSlotCompleted" => Ok(Self::RecruitSlotCompleted),
+            "RecruitError" => Ok(Self::RecruitError),
+            "EnterFacility" => de(&details).map(Self::EnterFacility),
+            "NotEnoughStaff" => de(&details).map(Self::NotEnoughStaff),
+            "ProductOfFacility" => de(&details).map(Self::ProductOfFacility),
+            "DepotInfo" => de(&details).map(Self::DepotInfo),
+            "OperBoxInfo" => de(&details).map(Self::OperBoxInfo),
+            "RoguelikeInvestment" => de(&details).map(Self::RoguelikeInvestment),
+            "RoguelikeSettlement" => de(&details).map(Self::RoguelikeSettlement),
+            "RoguelikeEvent" => de(&details).map(Self::RoguelikeEvent),
+            "RoguelikeCollapsalParadigms" => de(&details).map(Self::RoguelikeCollapsalParadigms),
+            "ReclamationReport" => de(&details).map(Self::ReclamationReport),
+            "ReclamationProcedureStart" => de(&details).map(Self::ReclamationProcedureStart),
+            "ReclamationSmeltGold" => de(&details).map(Self::ReclamationSmeltGold),
+            _ => return Self::Other { what, details },
+        };
+        info.unwrap_or_else(|e| {
+            log::warn!("parse {what} failed, keep raw details: {e}");
+            Self::Other { what, details }
+        })
+    }
+
+    /// 显示在 gui 中的信息，不需要显示的类型返回空
+    pub fn into_info(self) -> anyhow::Result<Option<String>> {
+        let info = match self {
+            Self::StageDrops(drops) => drops.into_info(),
+            Self::StageInfo(info) => info.into_info(),
+            Self::StageInfoError => Ok(i18n::tr("stage_info_error").to_string()),
+            Self::PenguinId(id) => id.into_info(),
+            Self::RecruitTagsDetected(tags) => tags.into_info(),
+            Self::RecruitSpecialTag(tag) => tag.into_info(),
+            Self::RecruitResult(result) => result.into_info(),
+            Self::RecruitTagsRefreshed(refreshed) => refreshed.into_info(),
+            Self::RecruitNoPermit(no_permit) => no_permit.into_info(),
+            Self::RecruitTagsSelected(tags) => tags.into_info(),
+            Self::RecruitSlotCompleted => Ok(i18n::tr("recruit_slot_completed").to_string()),
+            Self::RecruitError => Ok(i18n::tr("recruit_error").to_string()),
+            Self::EnterFacility(facility) => facility.into_info(),
+            Self::NotEnoughStaff(facility) => facility.into_info(),
+            Self::ProductOfFacility(product) => product.into_info(),
+            Self::DepotInfo(depot) => depot.into_info(),
+            Self::OperBoxInfo(oper_box) => oper_box.into_info(),
+            Self::RoguelikeInvestment(investment) => investment.into_info(),
+            Self::RoguelikeSettlement(settlement) => settlement.into_info(),
+            Self::RoguelikeEvent(event) => event.into_info(),
+            Self::RoguelikeCollapsalParadigms(paradigms) => paradigms.into_info(),
+            Self::ReclamationReport(report) => report.into_info(),
+            Self::ReclamationProcedureStart(start) => start.into_info(),
+            Self::ReclamationSmeltGold(smelt) => smelt.into_info(),
+            Self::Other { .. } => return Ok(None),
+        };
+        info.map(Some)
+    }
+}
+
+#[derive(Deserialize)]
+struct RawExtraInfo {
+    #[serde(default)]
+    taskchain: String,
+    class: Option<String>,
+    #[serde(default)]
+    taskid: i32,
+    uuid: Option<String>,
+    what: String,
+    #[serde(default)]
+    details: Value,
+}
+
+impl MaaEvent {
+    pub fn parse(code: AsstMsgCode, msg: &str) -> anyhow::Result<Self> {
+        fn de<T: serde::de::DeserializeOwned>(code: AsstMsgCode, msg: &str) -> anyhow::Result<T> {
+            serde_json::from_str(msg).with_context(|| format!("parse {code}"))
+        }
+
+        let event = match code {
+            AsstMsgCode::InternalError => Self::InternalError,
+            AsstMsgCode::InitFailed => Self::InitFailed,
+            AsstMsgCode::ConnectionInfo => Self::ConnectionInfo(de(code, msg)?),
+            AsstMsgCode::AllTasksCompleted => Self::AllTasksCompleted(de(code, msg)?),
+            AsstMsgCode::AsyncCallInfo => Self::AsyncCallInfo(de(code, msg)?),
+            AsstMsgCode::Destroyed => Self::Destroyed,
+            AsstMsgCode::TaskChainError => Self::TaskChainError(de(code, msg)?),
+            AsstMsgCode::TaskChainStart => Self::TaskChainStart(de(code, msg)?),
+            AsstMsgCode::TaskChainCompleted => Self::TaskChainCompleted(de(code, msg)?),
+            AsstMsgCode::TaskChainExtraInfo => Self::TaskChainExtraInfo(de(code, msg)?),
+            AsstMsgCode::TaskChainStopped => Self::TaskChainStopped(de(code, msg)?),
+            AsstMsgCode::SubTaskError => Self::SubTaskError(de(code, msg)?),
+            AsstMsgCode::SubTaskStart => Self::SubTaskStart(de(code, msg)?),
+            AsstMsgCode::SubTaskCompleted => Self::SubTaskCompleted(de(code, msg)?),
+            AsstMsgCode::SubTaskExtraInfo => {
+                let raw: RawExtraInfo = de(code, msg)?;
+                Self::SubTaskExtraInfo(SubTaskExtraEvent {
+                    taskchain: raw.taskchain,
+                    class: raw.class,
+                    taskid: raw.taskid,
+                    uuid: raw.uuid,
+                    info: ExtraInfo::parse(raw.what, raw.details),
+                })
+            }
+            AsstMsgCode::SubTaskStopped => Self::SubTaskStopped(de(code, msg)?),
+            AsstMsgCode::Unknown => Self::Unknown(de(code, msg).unwrap_or_default()),
+        };
+        Ok(event)
+    }
+}
+
+type Listener = Box<dyn Fn(&str, &MaaEvent) + Send + Sync>;
+
+static LISTENER: OnceLock<Listener> = OnceLock::new();
+
+/// 设置事件的监听者，参数为实例 id 和事件，只能设置一次
+pub fn set_listener(listener: impl Fn(&str, &MaaEvent) + Send + Sync + 'static) {
+    if LISTENER.set(Box::new(listener)).is_err() {
+        log::warn!("maa event listener is already set");
+    }
+}
+
+pub(crate) fn emit(instance: &str, event: &MaaEvent) {
+    if let Some(listener) = LISTENER.get() {
+        listener(instance, event);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn parse_events() {
+        let msg = r#"{"taskchain":"Fight","taskid":1,"uuid":"abc"}"#;
+        let event = MaaEvent::parse(AsstMsgCode::TaskChainStart, msg).unwrap();
+        let MaaEvent::TaskChainStart(task) = &event else {
+            panic!("{event:?}");
+        };
+        assert_eq!(task.chain_name(), "刷理智");
+        assert_eq!(
+            serde_json::to_value(&event).unwrap(),
+            json!({
+                "type": "TaskChainStart",
+                "details": { "taskchain": "Fight", "taskid": 1, "uuid": "abc" }
+            })
+        );
+
+        let msg = r#"{"what":"Connected","why":"","uuid":"","details":{}}"#;
+        assert!(matches!(
+            MaaEvent::parse(AsstMsgCode::ConnectionInfo, msg).unwrap(),
+            MaaEvent::ConnectionInfo(_)
+        ));
+        assert!(matches!(
+            MaaEvent::parse(AsstMsgCode::AllTasksCompleted, "{}").unwrap(),
+            MaaEvent::AllTasksCompleted(_)
+        ));
+        assert!(MaaEvent::parse(AsstMsgCode::TaskChainStart, "{}").is_err());
+    }
+
+    #[test]
+    fn parse_extra_info() {
+        let msg = json!({
+            "taskchain": "Fight",
+            "class": "asst::StageDropsTaskPlugin",
+            "taskid": 1,
+            "what": "StageDrops",
+            "details": {
+                "stage": { "stageCode": "1-7", "stageId": "main_01-07" },
+                "stars": 3,
+                "stats": [
+                    { "itemId": "30012", "itemName": "固源岩", "quantity": 102, "addQuantity": 2 }
+                ]
+            }
+        });
+        let event = MaaEvent::parse(AsstMsgCode::SubTaskExtraInfo, &msg.to_string()).unwrap();
+        let MaaEvent::SubTaskExtraInfo(extra) = &event else {
+            panic!("{event:?}");
+        };
+        assert!(matches!(extra.info, ExtraInfo::StageDrops(_)));
+        let value = serde_json::to_value(&event).unwrap();
+        assert_eq!(value["details"]["what"], "StageDrops");
+        assert_eq!(value["details"]["details"]["stars"], 3);
+
+        let msg = json!({ "taskchain": "Fight", "what": "Whatever", "details": { "a": 1 } });
+        let event = MaaEvent::parse(AsstMsgCode::SubTaskExtraInfo, &msg.to_string()).unwrap();
+        let value = serde_json::to_value(&event).unwrap();
+        assert_eq!(value["details"]["what"], "Whatever");
+        assert_eq!(value["details"]["details"]["a"], 1);
+
+        let msg =
+            json!({ "taskchain": "Fight", "what": "StageDrops", "details": { "stars": "x" } });
+        let event = MaaEvent::parse(AsstMsgCode::SubTaskExtraInfo, &msg.to_string()).unwrap();
+        let MaaEvent::SubTaskExtraInfo(extra) = &event else {
+            panic!("{event:?}");
+        };
+        let ExtraInfo::Other { what, details } = &extra.info else {
+            panic!("{event:?}");
+        };
+        assert_eq!(what, "StageDrops");
+        assert_eq!(details["stars"], "x");
+    }
+
+    macro_rules! fixtures {
+        ($($what:literal => $info:expr),* $(,)?) => {
+            [$((
+                $what,
+                include_str!(concat!("../fixtures/extra_info/", $what, ".json")),
+                $info,
+            )),*]
+        };
+    }
+
+    #[test]
+    fn display_extra_info_fixtures() {
+        let fixtures = fixtures![
+            "StageDrops" => "1-7: 3星通过\n材料掉落:\n[固源岩]+2 (总计:102)\n\n",
+            "StageInfo" => "开始战斗: 1-7",
+            "StageInfoError" => "关卡识别错误",
+            "PenguinId" => "企鹅物流 id: 12345678",
+            "RecruitTagsDetected" => "公招识别结果: 费用回复 防护 先锋干员 辅助干员 近战位",
+            "RecruitSpecialTag" => "公招提示: 高级资深干员",
+            "RecruitResult" => "公招标签 (4星):[\n    \"费用回复\",\n    \"防护\",\n    \"先锋干员\",\n    \"辅助干员\",\n    \"近战位\",\n]",
+            "RecruitTagsRefreshed" => "公招刷新标签: 第1次 (上限3次)",
+            "RecruitNoPermit" => "招聘许可不足，继续刷新标签",
+            "RecruitTagsSelected" => "选择tag：[\n    \"辅助干员\",\n]",
+            "RecruitSlotCompleted" => "当前公招栏位已完成",
+            "RecruitError" => "公招识别错误",
+            "EnterFacility" => "当前设施: 制造站 #0",
+            "NotEnoughStaff" => "制造站 #0 可用干员不足",
+            "ProductOfFacility" => "制造站 #0 当前产物: Money",
+            "DepotInfo" => "仓库识别完成，共 2 种物品",
+            "OperBoxInfo" => "干员识别完成，已拥有 1/2 名干员",
+            "RoguelikeInvestment" => "肉鸽投资 13 个源石锭，本轮共 26 个，当前存款 39",
+            "RoguelikeSettlement" => "肉鸽结算: 未通关，到达第 3 层，得分 356，招募 9 名干员，收集 4 个藏品",
+            "RoguelikeEvent" => "肉鸽事件: 风雪过境",
+            "RoguelikeCollapsalParadigms" => "坍缩范式加深: 目空一些",
+            "ReclamationReport" => "生息演算结算: 徽章 +60 (总计 1200)，建造点 +150 (总计 3000)",
+            "ReclamationProcedureStart" => "生息演算第 2 局开始",
+            "ReclamationSmeltGold" => "生息演算熔炼赤金 3 次",
+        ];
+        for (what, msg, info) in fixtures {
+            let event = MaaEvent::parse(AsstMsgCode::SubTaskExtraInfo, msg)
+                .unwrap_or_else(|e| panic!("{what}: {e:?}"));
+            let MaaEvent::SubTaskExtraInfo(extra) = event else {
+                panic!("{what}: {event:?}");
+            };
+            assert!(
+                !matches!(extra.info, ExtraInfo::Other { .. }),
+                "{what} is not parsed"
+            );
+            let value = serde_json::to_value(&extra).unwrap();
+            assert_eq!(value["what"], what);
+            assert_eq!(
+                extra.info.into_info().unwrap().as_deref(),
+                Some(info),
+                "{what}"
+            );
+        }
+    }
+}
@@ -0,0 +1,197 @@
+//! 回调消息的多语言文本，位于 `locales/<语言>.json`，以消息 id 为键
+//!
+//! 文本中的 `{name}` 会被替换为同名参数，缺少的文本回退到简体中文
+
+use std::{
+    collections::HashMap,
+    fmt::Display,
+    sync::{
+        LazyLock,
+        atomic::{AtomicU8, Ordering},
+    },
+};
+
+use serde::{Deserialize, Serialize};
+use strum::{Display, EnumIter, EnumString, FromRepr, IntoEnumIterator};
+
+#[derive(
+    Debug,
+    Default,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    Hash,
+    Display,
+    EnumString,
+    EnumIter,
+    FromRepr,
+    Serialize,
+    Deserialize,
+)]
+#[repr(u8)]
+pub enum Language {
+    #[default]
+    #[strum(serialize = "zh-CN")]
+    #[serde(rename = "zh-CN")]
+    ZhCn,
+    #[strum(serialize = "en-US")]
+    #[serde(rename = "en-US")]
+    EnUs,
+    #[strum(serialize = "ja-JP")]
+    #[serde(rename = "ja-JP")]
+    JaJp,
+}
+
+type Catalog = HashMap<String, String>;
+
+/// 在回调线程中使用，不能 panic，解析失败的语言只记录错误，文本回退到 id
+///
+/// 文本在编译时嵌入，由 `catalogs_are_complete` 测试保证能够解析
+static CATALOGS: LazyLock<HashMap<Language, Catalog>> = LazyLock::new(|| {
+    Language::iter()
+        .map(|lang| {
+            let catalog = serde_json::from_str(lang.source()).unwrap_or_else(|e| {
+                log::error!("invalid catalog {lang}: {e}");
+                Catalog::new()
+            });
+            (lang, catalog)
+        })
+        .collect()
+});
+
+static LANGUAGE: AtomicU8 = AtomicU8::new(Language::ZhCn as u8);
+
+/// 切换之后的消息使用的语言
+pub fn set_language(lang: Language) {
+    LANGUAGE.store(lang as u8, Ordering::Relaxed);
+}
+
+pub fn language() -> Language {
+    Language::from_repr(LANGUAGE.load(Ordering::Relaxed)).unwrap_or_default()
+}
+
+impl Language {
+    fn source(self) -> &'static str {
+        match self {
+            Language::ZhCn => include_str!("../locales/zh-CN.json"),
+            Language::EnUs => include_str!("../locales/en-US.json"),
+            Language::JaJp => include_str!("../locales/ja-JP.json"),
+        }
+    }
+
+    /// id 对应的文本，当前语言和简体中文都没有时为空
+    pub fn get(self, id: &str) -> Option<&'static str> {
+        [self, Language::ZhCn]
+            .into_iter()
+            .find_map(|lang| CATALOGS.get(&lang)?.get(id))
+            .map(String::as_str)
+    }
+
+    /// id 对应的文本，没有时返回 id
+    pub fn tr(self, id: &str) -> &str {
+        self.get(id).unwrap_or(id)
+    }
+
+    /// 替换文本中的参数，未提供的参数原样保留
+    pub fn format(self, id: &str, args: &[(&str, &dyn Display)]) -> String {
+        let mut template = self.tr(id);
+        let mut s = String::with_capacity(template.len());
+        while let Some(start) = template.find('{') {
+            s.push_str(&template[..start]);
+            let rest = &template[start..];
+            let arg = rest.find('}').and_then(|end| {
+                let (_, value) = args.iter().find(|(name, _)| *name == &rest[1..end])?;
+                Some((end, value))
+            });
+            match arg {
+                Some((end, value)) => {
+                    s.push_str(&value.to_string());
+                    template = &rest[end + 1..];
+                }
+                None => {
+                    s.push('{');
+                    template = &rest[1..];
+                }
+            }
+        }
+        s.push_str(template);
+        s
+    }
+}
+
+/// 当前语言下 id 对应的文本
+pub fn tr(id: &str) -> &str {
+    language().tr(id)
+}
+
+/// 当前语言下带参数的文本，如 `tr!("taskchain_start", name = name)`
+#[macro_export]
+macro_rules! tr {
+    ($id:expr $(, $name:ident = $value:expr)* $(,)?) => {
+        $crate::i18n::language().format(
+            $id,
+            &[$((stringify!($name), &$value as &dyn ::std::fmt::Display)),*],
+        )
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeSet;
+
+    use super::*;
+
+    fn placeholders(template: &str) -> BTreeSet<&str> {
+        template
+            .split('{')
+            .skip(1)
+            .filter_map(|s| s.split_once('}').map(|(name, _)| name))
+            .collect()
+    }
+
+    #[test]
+    fn catalogs_are_complete() {
+        let parse = |lang: Language| -> Catalog {
+            serde_json::from_str(lang.source())
+                .unwrap_or_else(|e| panic!("invalid catalog {lang}: {e}"))
+        };
+        let zh = &parse(Language::ZhCn);
+        assert!(!zh.is_empty());
+        for lang in Language::iter() {
+            let catalog = &parse(lang);
+            assert_eq!(catalog.len(), zh.len(), "{lang}");
+            for (id, template) in zh {
+                let translated = catalog
+                    .get(id)
+                    .unwrap_or_else(|| panic!("{id} is missing in {lang}"));
+                assert_eq!(
+                    placeholders(translated),
+                    placeholders(template),
+                    "{id} of {lang}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn format_messages() {
+        let name = "Fight";
+        let args: &[(&str, &dyn Display)] = &[("name", &name)];
+        assert_eq!(
+            Language::EnUs.format("taskchain_start", args),
+            "Task started: Fight"
+        );
+        assert_eq!(
+            Language::JaJp.format("taskchain_start", args),
+            "タスク開始：Fight"
+        );
+        assert_eq!(
+            Language::EnUs.format("taskchain_start", &[]),
+            "Task started: {name}"
+        );
+        assert_eq!(Language::EnUs.tr("facility.Mfg"), "Factory");
+        assert_eq!(Language::JaJp.tr("facility.Unknown"), "facility.Unknown");
+        assert_eq!("en-US".parse::<Language>().unwrap(), Language::EnUs);
+    }
+}
@@ -1,3 +1,5 @@
 pub mod callback;
 pub mod callback_types;
+pub mod event;
+pub mod i18n;
 pub mod msg_handler;
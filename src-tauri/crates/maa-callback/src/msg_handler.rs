@@ -1,54 +1,56 @@
 use anyhow::Context;
 use log::{error, info};
 
-use crate::{
-    callback::AsstMsgCode,
-    callback_types::{SubTask, SubTaskExtraInfo, TaskChainInfo},
-};
+use crate::{event::MaaEvent, i18n::tr, tr};
 
-pub fn notify(code: AsstMsgCode, msg: &str) -> anyhow::Result<()> {
-    match code {
-        AsstMsgCode::InternalError => error!("内部错误"),
-        AsstMsgCode::InitFailed => error!("初始化失败"),
-        AsstMsgCode::AllTasksCompleted => info!("全部任务完成"),
-        AsstMsgCode::TaskChainStart => {
-            let task: TaskChainInfo =
-                serde_json::from_str(msg).context("parse task chain start")?;
-            let task_name = task.get_task_chain_name();
-            info!("开始任务：{}", task_name);
+/// 显示在gui中的日志，实例 id 拼接在 target 后
+pub const GUI_TARGET: &str = module_path!();
+
+/// 从日志的 target 中取出实例 id
+pub fn instance_of(target: &str) -> Option<&str> {
+    target
+        .strip_prefix(GUI_TARGET)?
+        .strip_prefix("::")
+        .filter(|id| !id.is_empty())
+}
+
+pub fn notify(instance: &str, event: MaaEvent) -> anyhow::Result<()> {
+    let target = match instance {
+        "" => GUI_TARGET.to_string(),
+        id => format!("{GUI_TARGET}::{id}"),
+    };
+    let target = target.as_str();
+    match event {
+        MaaEvent::InternalError => error!(target: target, "{}", tr("internal_error")),
+        MaaEvent::InitFailed => error!(target: target, "{}", tr("init_failed")),
+        MaaEvent::AllTasksCompleted(_) => info!(target: target, "{}", tr("all_tasks_completed")),
+        MaaEvent::TaskChainStart(task) => {
+            info!(target: target, "{}", tr!("taskchain_start", name = task.chain_name()));
         }
-        AsstMsgCode::TaskChainCompleted => {
-            let task: TaskChainInfo =
-                serde_json::from_str(msg).context("parse task chain completed")?;
-            let task_name = task.get_task_chain_name();
-            info!("任务完成：{}", task_name);
+        MaaEvent::TaskChainCompleted(task) => {
+            info!(target: target, "{}", tr!("taskchain_completed", name = task.chain_name()));
         }
-        AsstMsgCode::TaskChainStopped => {
-            info!("已停止");
+        MaaEvent::TaskChainStopped(_) => {
+            info!(target: target, "{}", tr("taskchain_stopped"));
         }
-        AsstMsgCode::TaskChainError => {
-            let task: TaskChainInfo =
-                serde_json::from_str(msg).context("parse task chain error")?;
-            let task_name = task.get_task_chain_name();
-            error!("任务失败：{}", task_name);
+        MaaEvent::TaskChainError(task) => {
+            error!(target: target, "{}", tr!("taskchain_error", name = task.chain_name()));
         }
-        AsstMsgCode::SubTaskStart => {
-            let sub_task: SubTask = serde_json::from_str(msg).context("parse sub task")?;
+        MaaEvent::SubTaskStart(sub_task) => {
             sub_task
-                .get_task_info()
-                .context("get sub task Chinese info")?
-                .inspect(|i| info!("{i}"));
+                .task_info()
+                .context("get sub task info")?
+                .inspect(|i| info!(target: target, "{i}"));
         }
-        AsstMsgCode::ConnectionInfo => {} // TODO: 截图时间 adb相关
-        AsstMsgCode::SubTaskExtraInfo => {
-            let sub_task_ex: SubTaskExtraInfo =
-                serde_json::from_str(msg).context("parse sub task ex")?;
-            sub_task_ex
-                .to_exact_info()
-                .context("get sub task Chinese ex info")?
-                .inspect(|i| info!("{i}"));
+        MaaEvent::ConnectionInfo(_) => {} // TODO: 截图时间 adb相关
+        MaaEvent::SubTaskExtraInfo(extra) => {
+            extra
+                .info
+                .into_info()
+                .context("get sub task ex info")?
+                .inspect(|i| info!(target: target, "{i}"));
         }
-        AsstMsgCode::Unknown => error!("未知错误！"),
+        MaaEvent::Unknown(_) => error!(target: target, "{}", tr("unknown_error")),
         _ => {}
     }
     Ok(())
@@ -0,0 +1,190 @@
+//! 配置文件的原子写入和滚动备份
+//!
+//! 备份位于 `config/<profile>/backups/<cfg>.<timestamp>.json`
+
+use std::{
+    cmp::Reverse,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{Context, ensure};
+use chrono::{Local, NaiveDateTime};
+use log::{trace, warn};
+use serde::Serialize;
+use tokio::{fs, io::AsyncWriteExt};
+
+use crate::{CFG_SUFFIX, ConfigValue, load_json_obj};
+
+pub const BACKUP_DIR: &str = "backups";
+/// 每个配置文件保留的备份数量
+pub const MAX_BACKUPS: usize = 10;
+const TIMESTAMP_FORMAT: &str = "%Y%m%d-%H%M%S%3f";
+const TMP_SUFFIX: &str = ".tmp";
+
+#[derive(Debug, Serialize)]
+pub struct Backup {
+    /// 备份文件名，用于恢复
+    pub name: String,
+    /// 对应的配置名，如 `daily`
+    pub config: String,
+    pub time: NaiveDateTime,
+}
+
+impl Backup {
+    fn parse(file_name: &str) -> Option<Self> {
+        if file_name.contains(['/', '\\']) {
+            return None;
+        }
+        let stem = file_name.strip_suffix(CFG_SUFFIX)?;
+        let (config, time) = stem.split_once('.')?;
+        let time = NaiveDateTime::parse_from_str(time, TIMESTAMP_FORMAT).ok()?;
+        Some(Self {
+            name: file_name.to_string(),
+            config: config.to_string(),
+            time,
+        })
+    }
+}
+
+/// 先写入临时文件并落盘，再重命名覆盖目标文件
+pub async fn write_atomic(path: &Path, contents: impl AsRef<[u8]>) -> anyhow::Result<()> {
+    let mut tmp = path.as_os_str().to_owned();
+    tmp.push(TMP_SUFFIX);
+    let tmp = PathBuf::from(tmp);
+
+    let mut file = fs::File::create(&tmp)
+        .await
+        .with_context(|| format!("create {tmp:?}"))?;
+    file.write_all(contents.as_ref())
+        .await
+        .with_context(|| format!("write {tmp:?}"))?;
+    file.sync_all()
+        .await
+        .with_context(|| format!("sync {tmp:?}"))?;
+    drop(file);
+
+    fs::rename(&tmp, path)
+        .await
+        .with_context(|| format!("rename {tmp:?} to {path:?}"))
+}
+
+/// 备份已存在的配置文件，并清理超出数量的旧备份
+pub async fn backup(path: &Path, backup_dir: &Path) -> anyhow::Result<()> {
+    if !fs::try_exists(path).await.unwrap_or_default() {
+        return Ok(());
+    }
+    let config = config_name(path)?;
+    fs::create_dir_all(backup_dir)
+        .await
+        .context("create backup dir")?;
+
+    let time = Local::now().naive_local().format(TIMESTAMP_FORMAT);
+    let target = backup_dir.join(format!("{config}.{time}{CFG_SUFFIX}"));
+    trace!("backup {path:?} to {target:?}");
+    fs::copy(path, &target)
+        .await
+        .with_context(|| format!("copy to {target:?}"))?;
+
+    for old in list(backup_dir, Some(config))
+        .await?
+        .into_iter()
+        .skip(MAX_BACKUPS)
+    {
+        if let Err(e) = fs::remove_file(backup_dir.join(&old.name)).await {
+            warn!("remove old backup {}: {e}", old.name);
+        }
+    }
+    Ok(())
+}
+
+/// 迁移前的原始文件，保存为 `<cfg>.v<version>.json`，不参与滚动清理
+pub async fn keep_pre_migration(
+    path: &Path,
+    backup_dir: &Path,
+    version: u64,
+) -> anyhow::Result<()> {
+    if !fs::try_exists(path).await.unwrap_or_default() {
+        return Ok(());
+    }
+    let config = config_name(path)?;
+    fs::create_dir_all(backup_dir)
+        .await
+        .context("create backup dir")?;
+    let target = backup_dir.join(format!("{config}.v{version}{CFG_SUFFIX}"));
+    fs::copy(path, &target)
+        .await
+        .with_context(|| format!("copy to {target:?}"))?;
+    Ok(())
+}
+
+/// 列出备份，按时间从新到旧排序
+pub async fn list(backup_dir: &Path, config: Option<&str>) -> anyhow::Result<Vec<Backup>> {
+    let mut entries = match fs::read_dir(backup_dir).await {
+        Ok(entries) => entries,
+        Err(e) if matches!(e.kind(), std::io::ErrorKind::NotFound) => return Ok(vec![]),
+        Err(e) => return Err(e).context("read backup dir"),
+    };
+
+    let mut backups = vec![];
+    while let Some(entry) = entries.next_entry().await.context("read backup entry")? {
+        if let Some(backup) = entry.file_name().to_str().and_then(Backup::parse)
+            && config.is_none_or(|c| c == backup.config)
+        {
+            backups.push(backup);
+        }
+    }
+    backups.sort_by_key(|b| Reverse(b.time));
+    Ok(backups)
+}
+
+/// 读取指定备份
+pub async fn load(backup_dir: &Path, name: &str) -> anyhow::Result<(Backup, ConfigValue)> {
+    let backup = Backup::parse(name).with_context(|| format!("invalid backup name: {name}"))?;
+    ensure!(
+        fs::try_exists(backup_dir.join(&backup.name))
+            .await
+            .unwrap_or_default(),
+        "backup {name} does not exist"
+    );
+    let value = load_json_obj(backup_dir.join(&backup.name))
+        .await
+        .with_context(|| format!("load backup {name}"))?;
+    ensure!(value.is_object(), "backup {name} is not a json object");
+    Ok((backup, value))
+}
+
+/// 从新到旧查找第一个能正常解析的备份
+pub async fn latest_valid(
+    backup_dir: &Path,
+    config: &str,
+) -> anyhow::Result<Option<(Backup, ConfigValue)>> {
+    for backup in list(backup_dir, Some(config)).await? {
+        match load(backup_dir, &backup.name).await {
+            Ok(b) => return Ok(Some(b)),
+            Err(e) => warn!("skip broken backup {}: {e:?}", backup.name),
+        }
+    }
+    Ok(None)
+}
+
+fn config_name(path: &Path) -> anyhow::Result<&str> {
+    path.file_name()
+        .and_then(|n| n.to_str())
+        .and_then(|n| n.strip_suffix(CFG_SUFFIX))
+        .with_context(|| format!("invalid config path: {path:?}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn backup_name() {
+        let time = Local::now().naive_local().format(TIMESTAMP_FORMAT);
+        let backup = Backup::parse(&format!("extra-task.{time}.json")).unwrap();
+        assert_eq!(backup.config, "extra-task");
+
+        assert!(Backup::parse("daily.json").is_none());
+        assert!(Backup::parse("../daily.20250101-000000000.json").is_none());
+    }
+}
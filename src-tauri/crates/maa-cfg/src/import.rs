@@ -0,0 +1,38 @@
+//! 从其他 MAA 前端导入配置
+
+pub mod maa_cli;
+pub mod wpf;
+
+use serde::Serialize;
+
+use crate::ConfigValue;
+
+/// 导入结果，记录成功导入的配置项和无法映射的设置
+#[derive(Debug, Default, Serialize)]
+pub struct ImportReport {
+    /// 成功写入的配置项，如 `Fight`、`Adb`
+    pub imported: Vec<String>,
+    pub unmapped: Vec<Unmapped>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Unmapped {
+    pub key: String,
+    pub value: ConfigValue,
+    pub reason: String,
+}
+
+impl ImportReport {
+    pub fn unmapped(
+        &mut self,
+        key: impl Into<String>,
+        value: ConfigValue,
+        reason: impl Into<String>,
+    ) {
+        self.unmapped.push(Unmapped {
+            key: key.into(),
+            value,
+            reason: reason.into(),
+        });
+    }
+}
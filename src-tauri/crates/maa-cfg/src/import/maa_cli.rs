@@ -0,0 +1,346 @@
+//! 与 maa-cli 的任务文件 `tasks/*.toml` 和连接配置 `profiles/*.toml` 互相转换
+//!
+//! maa-cli 支持 toml 和 json 两种格式，按文件后缀区分。Maa-SE 没有条件任务，
+//! 导入时只合并无条件的变体，其他变体记录在报告中
+
+use std::path::Path;
+
+use anyhow::{Context, bail};
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
+use tokio::fs;
+
+use super::ImportReport;
+use crate::{
+    Config, ConfigType, ConfigValue, DAILY_CFG, EXTRA_TASK_CFG, Parameters, TASK_TYPE_KEY,
+    TaskType,
+    backup::write_atomic,
+    params::GUI_KEYS,
+    settings::{AdbSettings, ExtraAdb, SettingType},
+};
+
+type JsonObject = serde_json::Map<String, ConfigValue>;
+
+/// 导入时会按顺序依次处理的任务配置
+const TASK_CFGS: &[&str] = &[DAILY_CFG, EXTRA_TASK_CFG];
+const ALWAYS_CONDITION: &str = "Always";
+
+#[derive(Debug, Clone, Copy)]
+enum Format {
+    Toml,
+    Json,
+}
+
+impl Format {
+    fn from_path(path: &Path) -> anyhow::Result<Self> {
+        match path.extension().and_then(|e| e.to_str()) {
+            Some("toml") => Ok(Self::Toml),
+            Some("json") => Ok(Self::Json),
+            ext => bail!("unsupported file format: {ext:?}, expected toml or json"),
+        }
+    }
+
+    fn parse<T: DeserializeOwned>(self, content: &str) -> anyhow::Result<T> {
+        match self {
+            Format::Toml => toml::from_str(content).context("parse toml"),
+            Format::Json => serde_json::from_str(content).context("parse json"),
+        }
+    }
+
+    fn to_string<T: Serialize>(self, value: &T) -> anyhow::Result<String> {
+        match self {
+            Format::Toml => toml::to_string_pretty(value).context("serialize toml"),
+            Format::Json => serde_json::to_string_pretty(value).context("serialize json"),
+        }
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct TaskFile {
+    pub tasks: Vec<CliTask>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CliTask {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(rename = "type")]
+    pub type_: String,
+    #[serde(default)]
+    pub params: JsonObject,
+    /// `merge` 合并所有匹配变体的参数，`first` 只用第一个匹配的变体
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub strategy: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub variants: Vec<Variant>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Variant {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub condition: Option<ConfigValue>,
+    #[serde(default)]
+    pub params: JsonObject,
+}
+
+impl Variant {
+    fn is_always(&self) -> bool {
+        self.condition
+            .as_ref()
+            .is_none_or(|c| c["type"].as_str() == Some(ALWAYS_CONDITION))
+    }
+}
+
+impl CliTask {
+    /// 合并无条件的变体，返回最终参数
+    fn resolve(self, report: &mut ImportReport) -> JsonObject {
+        let mut params = self.params;
+        let first = self.strategy.as_deref() == Some("first");
+        for (i, variant) in self.variants.into_iter().enumerate() {
+            if !variant.is_always() {
+                report.unmapped(
+                    format!("{}.variants[{i}]", self.type_),
+                    serde_json::to_value(&variant).unwrap_or_default(),
+                    "conditional variants are not supported",
+                );
+                continue;
+            }
+            params.extend(variant.params);
+            if first {
+                break;
+            }
+        }
+        params
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct CliProfile {
+    #[serde(default)]
+    pub connection: CliConnection,
+    /// 其他配置项，如 `resource`、`static_options`
+    #[serde(flatten)]
+    pub others: JsonObject,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct CliConnection {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub preset: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub adb_path: Option<String>,
+    #[serde(default, alias = "device", skip_serializing_if = "Option::is_none")]
+    pub address: Option<String>,
+    #[serde(flatten)]
+    pub others: JsonObject,
+}
+
+impl Config {
+    /// 导入 maa-cli 任务文件，文件中的任务会被启用并按顺序排列，
+    /// 其余任务会被禁用。任务的 `name` 作为 id，重复时自动编号
+    pub async fn import_maa_cli_tasks(&self, path: &Path) -> anyhow::Result<ImportReport> {
+        let format = Format::from_path(path)?;
+        let content = fs::read_to_string(path)
+            .await
+            .with_context(|| format!("read {path:?}"))?;
+        let file: TaskFile = format.parse(&content)?;
+
+        let mut report = ImportReport::default();
+        let mut imported = vec![];
+        for (index, task) in file.tasks.into_iter().enumerate() {
+            let type_ = task.type_.clone();
+            let task_type = match type_.parse() {
+                Ok(TaskType::Custom(_)) | Err(()) => {
+                    report.unmapped(
+                        &type_,
+                        serde_json::to_value(&task).unwrap_or_default(),
+                        "unknown task type",
+                    );
+                    continue;
+                }
+                Ok(t) => t,
+            };
+            let base = task.name.clone().unwrap_or_else(|| type_.clone());
+            let id = (1..)
+                .map(|n| match n {
+                    1 => base.clone(),
+                    n => format!("{base}-{n}"),
+                })
+                .find(|id| !imported.contains(id))
+                .expect("infinite ids");
+
+            let mut extra = task.resolve(&mut report);
+            extra.insert("index".to_string(), index.into());
+            let value = ConfigValue::Object(extra.clone());
+            let params = Parameters {
+                enable: true,
+                extra: extra.into_iter().collect(),
+            };
+            match self.set_task_impl(id.clone(), task_type, params).await {
+                Ok(()) => {
+                    report.imported.push(id.clone());
+                    imported.push(id);
+                }
+                Err(e) => report.unmapped(id, value, format!("{e:#}")),
+            }
+        }
+
+        for cfg in TASK_CFGS {
+            for (id, params) in self.task_entries(cfg) {
+                if imported.contains(&id) || !params["enable"].as_bool().unwrap_or_default() {
+                    continue;
+                }
+                let value = params.clone();
+                let mut params: Parameters =
+                    serde_json::from_value(params).context("parse stored params")?;
+                params.enable = false;
+                if let Err(e) = self.set_task(&id, params).await {
+                    report.unmapped(id, value, format!("failed to disable: {e:#}"));
+                }
+            }
+        }
+        Ok(report)
+    }
+
+    /// 导出已启用的任务为 maa-cli 任务文件
+    pub async fn export_maa_cli_tasks(&self, path: &Path) -> anyhow::Result<()> {
+        let format = Format::from_path(path)?;
+        let tasks = TASK_CFGS
+            .iter()
+            .flat_map(|cfg| self.enabled_task_entries(cfg))
+            .map(|(id, params)| {
+                let mut params = match params {
+                    ConfigValue::Object(map) => map,
+                    _ => JsonObject::new(),
+                };
+                params.remove("enable");
+                GUI_KEYS.iter().for_each(|k| {
+                    params.remove(*k);
+                });
+                let type_ = match params.remove(TASK_TYPE_KEY) {
+                    Some(ConfigValue::String(t)) => t,
+                    _ => id.clone(),
+                };
+                CliTask {
+                    name: (id != type_).then_some(id),
+                    type_,
+                    params,
+                    strategy: None,
+                    variants: vec![],
+                }
+            })
+            .collect();
+
+        let contents = format.to_string(&TaskFile { tasks })?;
+        write_atomic(path, contents).await
+    }
+
+    /// 导入 maa-cli 连接配置中的 adb 设置
+    pub async fn import_maa_cli_profile(&self, path: &Path) -> anyhow::Result<ImportReport> {
+        let format = Format::from_path(path)?;
+        let content = fs::read_to_string(path)
+            .await
+            .with_context(|| format!("read {path:?}"))?;
+        let profile: CliProfile = format.parse(&content)?;
+
+        let mut report = ImportReport::default();
+        let CliConnection {
+            preset,
+            adb_path,
+            address,
+            others,
+        } = profile.connection;
+        if let Some(preset) = preset {
+            report.unmapped(
+                "connection.preset",
+                preset.into(),
+                "presets are not supported, using general adb",
+            );
+        }
+        for (key, value) in others {
+            report.unmapped(format!("connection.{key}"), value, "not supported");
+        }
+        for (key, value) in profile.others {
+            report.unmapped(key, value, "not supported");
+        }
+
+        let default = AdbSettings::default();
+        let adb = AdbSettings {
+            path: adb_path.map(Into::into).unwrap_or(default.path),
+            address: address.unwrap_or(default.address),
+            extra: ExtraAdb::None,
+        };
+        let value = serde_json::to_value(adb).context("serde adb settings")?;
+        let params = Parameters {
+            enable: true,
+            extra: serde_json::from_value(value).context("adb settings to params")?,
+        };
+        self.set_and_write(ConfigType::Settings(SettingType::Adb), params)
+            .await
+            .context("write adb settings")?;
+        report.imported.push(SettingType::Adb.to_string());
+        Ok(report)
+    }
+
+    /// 导出 adb 设置为 maa-cli 连接配置，模拟器相关的额外配置无法导出
+    pub async fn export_maa_cli_profile(&self, path: &Path) -> anyhow::Result<ImportReport> {
+        let format = Format::from_path(path)?;
+        let adb = self.adb_config()?;
+
+        let mut report = ImportReport::default();
+        if !matches!(adb.extra, ExtraAdb::None) {
+            report.unmapped(
+                "extra",
+                serde_json::to_value(&adb.extra).context("serde extra adb")?,
+                "emulator connection extras are not supported by maa-cli",
+            );
+        }
+
+        let profile = CliProfile {
+            connection: CliConnection {
+                preset: None,
+                adb_path: Some(adb.path.to_string_lossy().to_string()),
+                address: Some(adb.address),
+                others: JsonObject::new(),
+            },
+            others: JsonObject::new(),
+        };
+        let contents = format.to_string(&profile)?;
+        write_atomic(path, contents).await?;
+        report.imported.push(SettingType::Adb.to_string());
+        Ok(report)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn resolve_variants() {
+        let file: TaskFile = Format::Toml
+            .parse(
+                r#"
+                [[tasks]]
+                type = "Fight"
+                params = { stage = "1-7" }
+
+                [[tasks.variants]]
+                params = { medicine = 2 }
+
+                [[tasks.variants]]
+                condition = { type = "Weekday", weekdays = ["Tue"] }
+                params = { stage = "CE-6" }
+                "#,
+            )
+            .unwrap();
+        let mut report = ImportReport::default();
+        let task = file.tasks.into_iter().next().unwrap();
+        let params = task.resolve(&mut report);
+
+        assert_eq!(params["stage"], json!("1-7"));
+        assert_eq!(params["medicine"], json!(2));
+        assert_eq!(report.unmapped[0].key, "Fight.variants[1]");
+    }
+}
@@ -0,0 +1,592 @@
+//! 导入官方 WPF GUI 的 `config/gui.json`
+//!
+//! WPF 的设置项是扁平的 `"分组.名称": "值"`，值基本都以字符串保存
+
+use std::{
+    collections::HashSet,
+    path::{Path, PathBuf},
+};
+
+use anyhow::Context;
+use serde_json::json;
+use tokio::fs;
+
+use super::ImportReport;
+use crate::{
+    Config, ConfigType, ConfigValue, DailyTaskType, Parameters, TaskType,
+    settings::{AdbSettings, ExtraAdb, SettingType, mumu::MuMuEmulator12ConnectionExtras},
+};
+
+type JsonObject = serde_json::Map<String, ConfigValue>;
+
+pub const DEFAULT_CONFIGURATION: &str = "Default";
+const MUMU12_PRESET: &str = "MuMuEmulator12";
+const GENERAL_PRESET: &str = "General";
+
+/// WPF 任务队列中的任务名
+const TASK_QUEUE: &[(&str, &str)] = &[
+    ("WakeUp", "StartUp"),
+    ("Recruiting", "Recruit"),
+    ("Base", "Infrast"),
+    ("Combat", "Fight"),
+    ("Mall", "Mall"),
+    ("Mission", "Award"),
+    ("AutoRoguelike", "Roguelike"),
+    ("Reclamation", "Reclamation"),
+];
+
+const FACILITIES: &[&str] = &[
+    "Mfg",
+    "Trade",
+    "Control",
+    "Power",
+    "Reception",
+    "Office",
+    "Dorm",
+    "Processing",
+    "Training",
+];
+
+pub struct WpfImport {
+    pub tasks: Vec<(DailyTaskType, Parameters)>,
+    pub adb: Option<AdbSettings>,
+    pub report: ImportReport,
+}
+
+/// 记录读取过的设置项，剩余的就是无法映射的
+struct Source<'a> {
+    values: &'a JsonObject,
+    used: HashSet<&'a str>,
+    report: ImportReport,
+}
+
+impl<'a> Source<'a> {
+    fn raw(&mut self, key: &str) -> Option<&'a ConfigValue> {
+        let (k, v) = self.values.get_key_value(key)?;
+        self.used.insert(k.as_str());
+        Some(v)
+    }
+
+    fn string(&mut self, key: &str) -> Option<String> {
+        self.raw(key).map(|v| match v {
+            ConfigValue::String(s) => s.clone(),
+            v => v.to_string(),
+        })
+    }
+
+    fn bool(&mut self, key: &str) -> Option<bool> {
+        let value = self.raw(key)?;
+        let parsed = match value {
+            ConfigValue::Bool(b) => Some(*b),
+            ConfigValue::String(s) if s.eq_ignore_ascii_case("true") => Some(true),
+            ConfigValue::String(s) if s.eq_ignore_ascii_case("false") => Some(false),
+            _ => None,
+        };
+        if parsed.is_none() {
+            self.report.unmapped(key, value.clone(), "expected bool");
+        }
+        parsed
+    }
+
+    fn int(&mut self, key: &str) -> Option<i64> {
+        let value = self.raw(key)?;
+        let parsed = match value {
+            ConfigValue::Number(n) => n.as_i64(),
+            ConfigValue::String(s) => s.trim().parse().ok(),
+            _ => None,
+        };
+        if parsed.is_none() {
+            self.report.unmapped(key, value.clone(), "expected integer");
+        }
+        parsed
+    }
+
+    fn list(&mut self, key: &str) -> Option<Vec<String>> {
+        self.string(key).map(|s| {
+            s.split([';', '；'])
+                .map(str::trim)
+                .filter(|s| !s.is_empty())
+                .map(ToString::to_string)
+                .collect()
+        })
+    }
+
+    fn finish(mut self) -> ImportReport {
+        for (key, value) in self.values {
+            if !self.used.contains(key.as_str()) {
+                self.report
+                    .unmapped(key, value.clone(), "no matching setting in Maa-SE");
+            }
+        }
+        self.report
+    }
+}
+
+fn put(map: &mut JsonObject, field: &str, value: Option<impl Into<ConfigValue>>) {
+    if let Some(v) = value {
+        map.insert(field.to_string(), v.into());
+    }
+}
+
+/// 解析 gui.json，`configuration` 为空时使用 WPF 当前选中的配置
+pub fn parse(content: &str, configuration: Option<&str>) -> anyhow::Result<WpfImport> {
+    let root: ConfigValue = serde_json::from_str(content).context("parse gui.json")?;
+    // 旧版本没有多配置，设置项直接位于根节点
+    let values = match root.get("Configurations") {
+        Some(cfgs) => {
+            let name = configuration
+                .or_else(|| root["Current"].as_str())
+                .unwrap_or(DEFAULT_CONFIGURATION);
+            cfgs.get(name)
+                .and_then(|c| c.as_object())
+                .with_context(|| format!("configuration '{name}' not found"))?
+        }
+        None => root.as_object().context("gui.json is not an object")?,
+    };
+
+    let mut src = Source {
+        values,
+        used: HashSet::new(),
+        report: ImportReport::default(),
+    };
+
+    let mut tasks = vec![];
+    for (wpf_name, name) in TASK_QUEUE {
+        let task: DailyTaskType = name.parse().expect("valid daily task type");
+        let mut map = match task {
+            DailyTaskType::StartUp => start_up(&mut src),
+            DailyTaskType::Recruit => recruit(&mut src),
+            DailyTaskType::Infrast => infrast(&mut src),
+            DailyTaskType::Fight => fight(&mut src),
+            DailyTaskType::Mall => mall(&mut src),
+            DailyTaskType::Award => award(&mut src),
+            DailyTaskType::Roguelike => roguelike(&mut src),
+            DailyTaskType::Reclamation => reclamation(&mut src),
+            DailyTaskType::CloseDown => unreachable!("not in wpf task queue"),
+        };
+        put(
+            &mut map,
+            "index",
+            src.int(&format!("TaskQueue.Order.{wpf_name}")),
+        );
+        let enable = src
+            .bool(&format!("TaskQueue.{wpf_name}.IsChecked"))
+            .unwrap_or_default();
+        tasks.push((task, Parameters {
+            enable,
+            extra: map.into_iter().collect(),
+        }));
+    }
+
+    let adb = adb(&mut src);
+    Ok(WpfImport {
+        tasks,
+        adb,
+        report: src.finish(),
+    })
+}
+
+fn start_up(src: &mut Source) -> JsonObject {
+    let mut map = JsonObject::new();
+    put(&mut map, "client_type", src.string("Start.ClientType"));
+    put(&mut map, "start_game_enabled", src.bool("Start.StartGame"));
+    put(&mut map, "account_name", src.string("Start.AccountName"));
+    map
+}
+
+fn recruit(src: &mut Source) -> JsonObject {
+    let mut map = JsonObject::new();
+    put(&mut map, "refresh", src.bool("AutoRecruit.RefreshLevel3"));
+    put(&mut map, "times", src.int("AutoRecruit.MaxTimes"));
+    put(&mut map, "expedite", src.bool("AutoRecruit.UseExpedited"));
+
+    // 与 WPF 一致：总是点 4 星标签，按勾选确认各星级
+    let level1 = !src.bool("AutoRecruit.NotChooseLevel1").unwrap_or(true);
+    let level3 = src.bool("AutoRecruit.ChooseLevel3").unwrap_or(true);
+    let level4 = src.bool("AutoRecruit.ChooseLevel4").unwrap_or(true);
+    let level5 = src.bool("AutoRecruit.ChooseLevel5").unwrap_or(false);
+    let mut select = vec![4];
+    if level5 {
+        select.push(5);
+    }
+    let confirm = [(1, level1), (3, level3), (4, level4), (5, level5)]
+        .into_iter()
+        .filter_map(|(level, checked)| checked.then_some(level))
+        .collect::<Vec<_>>();
+    put(&mut map, "select", Some(select));
+    put(&mut map, "confirm", Some(confirm));
+    put(&mut map, "skip_robot", Some(!level1));
+
+    if src
+        .bool("AutoRecruit.IsLevel3UseShortTime")
+        .unwrap_or_default()
+    {
+        put(&mut map, "recruitment_time", Some(json!({ "3": 460 })));
+    }
+    put(
+        &mut map,
+        "report_to_penguin",
+        src.bool("Penguin.EnablePenguin"),
+    );
+    put(&mut map, "penguin_id", src.string("Penguin.Id"));
+    map
+}
+
+fn infrast(src: &mut Source) -> JsonObject {
+    let mut map = JsonObject::new();
+    // 设施按 WPF 中拖拽的顺序排列
+    let mut facilities = vec![];
+    for facility in FACILITIES {
+        let checked = src
+            .bool(&format!("Infrast.{facility}.IsChecked"))
+            .unwrap_or(!matches!(*facility, "Processing" | "Training"));
+        let order = src.int(&format!("Infrast.Order.{facility}"));
+        if checked {
+            facilities.push((order.unwrap_or(i64::MAX), facility.to_string()));
+        }
+    }
+    facilities.sort_by_key(|(order, _)| *order);
+    put(
+        &mut map,
+        "facility",
+        Some(facilities.into_iter().map(|(_, f)| f).collect::<Vec<_>>()),
+    );
+
+    put(&mut map, "drones", src.string("Infrast.UsesOfDrones"));
+    // WPF 的阈值是百分比
+    put(
+        &mut map,
+        "threshold",
+        src.int("Infrast.DormThreshold").map(|t| t as f64 / 100.0),
+    );
+    put(
+        &mut map,
+        "replenish",
+        src.bool("Infrast.OriginiumShardAutoReplenishment"),
+    );
+    put(
+        &mut map,
+        "dorm_notstationed_enabled",
+        src.bool("Infrast.DormFilterNotStationedEnabled"),
+    );
+    put(
+        &mut map,
+        "dorm_trust_enabled",
+        src.bool("Infrast.DormTrustEnabled"),
+    );
+    put(
+        &mut map,
+        "reception_message_board",
+        src.bool("Infrast.ReceptionMessageBoardReceive"),
+    );
+    if src.bool("Infrast.CustomInfrastEnabled").unwrap_or_default() {
+        put(&mut map, "mode", Some(10000));
+    }
+    put(
+        &mut map,
+        "filename",
+        src.string("Infrast.CustomInfrastFile"),
+    );
+    put(
+        &mut map,
+        "plan_index",
+        src.int("Infrast.CustomInfrastPlanIndex"),
+    );
+    map
+}
+
+fn fight(src: &mut Source) -> JsonObject {
+    let mut map = JsonObject::new();
+    put(&mut map, "stage", src.string("MainFunction.Stage1"));
+
+    let medicine = src.int("MainFunction.UseMedicine.Quantity");
+    if src.bool("MainFunction.UseMedicine").unwrap_or_default() {
+        put(&mut map, "medicine", medicine);
+    }
+    let stone = src.int("MainFunction.UseStone.Quantity");
+    if src.bool("MainFunction.UseStone").unwrap_or_default() {
+        put(&mut map, "stone", stone);
+    }
+    let times = src.int("MainFunction.TimesLimited.Quantity");
+    if src.bool("MainFunction.TimesLimited").unwrap_or_default() {
+        put(&mut map, "times", times);
+    }
+    put(&mut map, "series", src.int("MainFunction.Series.Quantity"));
+
+    let item = src.string("MainFunction.Drops.ItemId");
+    let quantity = src.int("MainFunction.Drops.Quantity");
+    if src.bool("MainFunction.Drops.Enable").unwrap_or_default()
+        && let (Some(item), Some(quantity)) = (item, quantity)
+        && !item.is_empty()
+    {
+        put(&mut map, "drops", Some(json!({ item: quantity })));
+    }
+
+    put(
+        &mut map,
+        "report_to_penguin",
+        src.bool("Penguin.EnablePenguin"),
+    );
+    put(&mut map, "penguin_id", src.string("Penguin.Id"));
+    put(&mut map, "client_type", src.string("Start.ClientType"));
+    map
+}
+
+fn mall(src: &mut Source) -> JsonObject {
+    let mut map = JsonObject::new();
+    put(&mut map, "shopping", src.bool("Mall.CreditShopping"));
+    put(&mut map, "buy_first", src.list("Mall.CreditFirstListNew"));
+    put(&mut map, "blacklist", src.list("Mall.CreditBlackListNew"));
+    put(
+        &mut map,
+        "force_shopping_if_credit_full",
+        src.bool("Mall.CreditForceShoppingIfCreditFull"),
+    );
+    put(
+        &mut map,
+        "only_buy_discount",
+        src.bool("Mall.CreditOnlyBuyDiscount"),
+    );
+    put(
+        &mut map,
+        "reserve_max_credit",
+        src.bool("Mall.CreditReserveMaxCredit"),
+    );
+    put(
+        &mut map,
+        "visit_friends",
+        src.bool("Mall.CreditVisitFriends"),
+    );
+    put(
+        &mut map,
+        "credit_fight",
+        src.bool("Mall.CreditFightTaskEnabled"),
+    );
+    map
+}
+
+fn award(src: &mut Source) -> JsonObject {
+    let mut map = JsonObject::new();
+    put(&mut map, "award", src.bool("Mission.ReceiveAward"));
+    put(&mut map, "mail", src.bool("Mission.ReceiveMail"));
+    put(&mut map, "recruit", src.bool("Mission.ReceiveFreeRecruit"));
+    put(&mut map, "orundum", src.bool("Mission.ReceiveOrundum"));
+    put(&mut map, "mining", src.bool("Mission.ReceiveMining"));
+    put(
+        &mut map,
+        "specialaccess",
+        src.bool("Mission.ReceiveSpecialAccess"),
+    );
+    map
+}
+
+fn roguelike(src: &mut Source) -> JsonObject {
+    let mut map = JsonObject::new();
+    put(&mut map, "theme", src.string("Roguelike.RoguelikeTheme"));
+    put(&mut map, "mode", src.int("Roguelike.Mode"));
+    put(&mut map, "squad", src.string("Roguelike.Squad"));
+    put(&mut map, "roles", src.string("Roguelike.Roles"));
+    put(&mut map, "core_char", src.string("Roguelike.CoreChar"));
+    put(&mut map, "difficulty", src.int("Roguelike.Difficulty"));
+    put(
+        &mut map,
+        "starts_count",
+        src.int("Roguelike.StartTimesLimit"),
+    );
+    put(
+        &mut map,
+        "investment_enabled",
+        src.bool("Roguelike.InvestmentEnabled"),
+    );
+    put(
+        &mut map,
+        "investments_count",
+        src.int("Roguelike.InvestsTimesLimit"),
+    );
+    put(
+        &mut map,
+        "stop_when_investment_full",
+        src.bool("Roguelike.StopWhenInvestmentFull"),
+    );
+    put(
+        &mut map,
+        "use_support",
+        src.bool("Roguelike.RoguelikeUseSupportUnit"),
+    );
+    put(
+        &mut map,
+        "use_nonfriend_support",
+        src.bool("Roguelike.RoguelikeUseNonfriendSupport"),
+    );
+    put(
+        &mut map,
+        "refresh_trader_with_dice",
+        src.bool("Roguelike.RefreshTraderWithDice"),
+    );
+    map
+}
+
+fn reclamation(src: &mut Source) -> JsonObject {
+    let mut map = JsonObject::new();
+    put(&mut map, "theme", src.string("Reclamation.Theme"));
+    put(&mut map, "mode", src.int("Reclamation.Mode"));
+    put(
+        &mut map,
+        "tools_to_craft",
+        src.list("Reclamation.ToolToCraft"),
+    );
+    put(
+        &mut map,
+        "increment_mode",
+        src.int("Reclamation.IncrementMode"),
+    );
+    put(
+        &mut map,
+        "num_craft_batches",
+        src.int("Reclamation.MaxCraftCountPerRound"),
+    );
+    map
+}
+
+fn adb(src: &mut Source) -> Option<AdbSettings> {
+    let path = src.string("Connect.AdbPath");
+    let address = src.string("Connect.Address");
+    if path.is_none() && address.is_none() {
+        return None;
+    }
+
+    let default = AdbSettings::default();
+    let preset = src.string("Connect.ConnectConfig");
+    let mumu_path = src.string("Connect.MuMu12EmulatorPath");
+    let mumu_enable = src.bool("Connect.MuMu12ExtrasEnabled");
+    let bridge = src.bool("Connect.MumuBridgeConnection");
+    let bridge_index = src.int("Connect.MuMu12Index");
+
+    let extra = match preset.as_deref() {
+        Some(MUMU12_PRESET) => ExtraAdb::MuMuEmulator12(MuMuEmulator12ConnectionExtras::new(
+            mumu_enable.unwrap_or(true),
+            mumu_path
+                .map(PathBuf::from)
+                .unwrap_or_else(|| PathBuf::from(crate::settings::mumu::DEFAULT_EMULATOR_PATH)),
+            bridge
+                .unwrap_or_default()
+                .then_some(bridge_index)
+                .flatten()
+                .and_then(|i| usize::try_from(i).ok()),
+        )),
+        Some(GENERAL_PRESET) | None => ExtraAdb::None,
+        Some(other) => {
+            src.report.unmapped(
+                "Connect.ConnectConfig",
+                other.into(),
+                "connection preset not supported, using general adb",
+            );
+            ExtraAdb::None
+        }
+    };
+
+    Some(AdbSettings {
+        path: path.map(PathBuf::from).unwrap_or(default.path),
+        address: address.unwrap_or(default.address),
+        extra,
+    })
+}
+
+impl Config {
+    /// 导入 WPF 的 gui.json 到当前配置组，校验失败的任务会记录在报告中
+    pub async fn import_wpf(
+        &self,
+        path: &Path,
+        configuration: Option<&str>,
+    ) -> anyhow::Result<ImportReport> {
+        let content = fs::read_to_string(path)
+            .await
+            .with_context(|| format!("read {path:?}"))?;
+        let WpfImport {
+            tasks,
+            adb,
+            mut report,
+        } = parse(&content, configuration)?;
+
+        for (task, params) in tasks {
+            let name = task.to_string();
+            let value = serde_json::to_value(&params).context("serde params")?;
+            match self
+                .set_and_write(ConfigType::Task(TaskType::Daily(task)), params)
+                .await
+            {
+                Ok(()) => report.imported.push(name),
+                Err(e) => report.unmapped(name, value, format!("{e:#}")),
+            }
+        }
+
+        if let Some(adb) = adb {
+            let value = serde_json::to_value(adb).context("serde adb settings")?;
+            let params = Parameters {
+                enable: true,
+                extra: serde_json::from_value(value).context("adb settings to params")?,
+            };
+            self.set_and_write(ConfigType::Settings(SettingType::Adb), params)
+                .await
+                .context("write adb settings")?;
+            report.imported.push(SettingType::Adb.to_string());
+        }
+        Ok(report)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_gui_json() {
+        let gui = json!({
+            "Current": "Farm",
+            "Configurations": {
+                "Farm": {
+                    "TaskQueue.Combat.IsChecked": "True",
+                    "TaskQueue.Order.Combat": "3",
+                    "MainFunction.Stage1": "CE-6",
+                    "MainFunction.UseMedicine": "True",
+                    "MainFunction.UseMedicine.Quantity": "2",
+                    "Infrast.DormThreshold": "30",
+                    "Connect.AdbPath": "adb",
+                    "Connect.Address": "127.0.0.1:16416",
+                    "Connect.ConnectConfig": "MuMuEmulator12",
+                    "GUI.UseTray": "True",
+                },
+            },
+        });
+        let import = parse(&gui.to_string(), None).unwrap();
+
+        let (_, fight) = import
+            .tasks
+            .iter()
+            .find(|(t, _)| matches!(t, DailyTaskType::Fight))
+            .unwrap();
+        assert!(fight.enable);
+        assert_eq!(fight.extra["stage"], "CE-6");
+        assert_eq!(fight.extra["medicine"], 2);
+        assert_eq!(fight.extra["index"], 3);
+
+        let (_, infrast) = import
+            .tasks
+            .iter()
+            .find(|(t, _)| matches!(t, DailyTaskType::Infrast))
+            .unwrap();
+        assert_eq!(infrast.extra["threshold"], 0.3);
+
+        let adb = import.adb.unwrap();
+        assert_eq!(adb.address, "127.0.0.1:16416");
+        assert!(matches!(adb.extra, ExtraAdb::MuMuEmulator12(_)));
+
+        let unmapped = import
+            .report
+            .unmapped
+            .iter()
+            .map(|u| u.key.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(unmapped, ["GUI.UseTray"]);
+    }
+}
@@ -0,0 +1,356 @@
+//! 按次追加的运行记录，每行一条 json，位于 `config/<profile>/<ledger>.jsonl`
+//!
+//! 与配置文件不同，记录只追加不修改，也不加载到缓存中
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt::Write,
+    path::PathBuf,
+};
+
+use anyhow::Context;
+use chrono::{DateTime, Local, NaiveDate};
+use log::warn;
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
+use tokio::{
+    fs::{self, OpenOptions},
+    io::AsyncWriteExt,
+};
+
+use crate::{Config, csv_field};
+
+pub const LEDGER_SUFFIX: &str = ".jsonl";
+pub const STAGE_DROPS_LEDGER: &str = "stage-drops";
+pub const RECRUIT_LEDGER: &str = "recruit";
+
+impl Config {
+    fn ledger_path(&self, ledger: &str) -> PathBuf {
+        self.path.join(format!("{ledger}{LEDGER_SUFFIX}"))
+    }
+
+    /// 追加一条记录
+    pub async fn append_ledger<T: Serialize>(
+        &self,
+        ledger: &str,
+        record: &T,
+    ) -> anyhow::Result<()> {
+        let path = self.ledger_path(ledger);
+        let mut line = serde_json::to_string(record).context("serde ledger record")?;
+        line.push('\n');
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .await
+            .with_context(|| format!("open {path:?}"))?;
+        file.write_all(line.as_bytes())
+            .await
+            .with_context(|| format!("append {path:?}"))
+    }
+
+    /// 读取全部记录，无法解析的行会被跳过
+    pub async fn read_ledger<T: DeserializeOwned>(&self, ledger: &str) -> anyhow::Result<Vec<T>> {
+        let path = self.ledger_path(ledger);
+        let content = match fs::read_to_string(&path).await {
+            Ok(s) => s,
+            Err(e) if matches!(e.kind(), std::io::ErrorKind::NotFound) => return Ok(vec![]),
+            Err(e) => return Err(e).with_context(|| format!("read {path:?}")),
+        };
+        Ok(content
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty())
+            .filter_map(|(i, line)| {
+                serde_json::from_str(line)
+                    .inspect_err(|e| warn!("skip line {} of {ledger}: {e}", i + 1))
+                    .ok()
+            })
+            .collect())
+    }
+
+    /// 时间范围内各关卡的掉落统计，范围两端为空时不限制
+    ///
+    /// `ap_costs` 以关卡名为键，在记录中没有理智消耗时使用
+    pub async fn stage_drop_stats(
+        &self,
+        from: Option<DateTime<Local>>,
+        to: Option<DateTime<Local>>,
+        ap_costs: &HashMap<String, u32>,
+    ) -> anyhow::Result<Vec<StageDropStats>> {
+        let records: Vec<StageDropRecord> = self.read_ledger(STAGE_DROPS_LEDGER).await?;
+        Ok(StageDropStats::collect(
+            records
+                .iter()
+                .filter(|r| from.is_none_or(|from| r.time >= from))
+                .filter(|r| to.is_none_or(|to| r.time < to)),
+            ap_costs,
+        ))
+    }
+
+    /// 时间范围内的公招统计，范围两端为空时不限制
+    pub async fn recruit_summary(
+        &self,
+        from: Option<DateTime<Local>>,
+        to: Option<DateTime<Local>>,
+    ) -> anyhow::Result<RecruitSummary> {
+        let records: Vec<RecruitRecord> = self.read_ledger(RECRUIT_LEDGER).await?;
+        Ok(RecruitSummary::collect(
+            records
+                .iter()
+                .filter(|r| from.is_none_or(|from| r.time >= from))
+                .filter(|r| to.is_none_or(|to| r.time < to)),
+        ))
+    }
+}
+
+/// 一次作战的掉落
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StageDropRecord {
+    pub time: DateTime<Local>,
+    pub stage_code: String,
+    #[serde(default)]
+    pub stage_id: String,
+    pub stars: u8,
+    /// 单次作战的理智消耗，未知时为空
+    #[serde(default)]
+    pub ap_cost: Option<u32>,
+    /// 物品名及本次掉落数量
+    pub drops: BTreeMap<String, u32>,
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct StageDropStats {
+    pub stage_code: String,
+    pub runs: u32,
+    /// 星级及对应的次数
+    pub stars: BTreeMap<u8, u32>,
+    /// 有任一次作战的理智消耗未知时为空
+    pub sanity: Option<u32>,
+    pub items: Vec<ItemDropStats>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ItemDropStats {
+    pub name: String,
+    pub count: u32,
+    pub per_run: f64,
+    pub per_sanity: Option<f64>,
+}
+
+impl StageDropStats {
+    /// 按关卡汇总，结果按关卡名排序
+    pub fn collect<'a>(
+        records: impl IntoIterator<Item = &'a StageDropRecord>,
+        ap_costs: &HashMap<String, u32>,
+    ) -> Vec<Self> {
+        let mut stages: BTreeMap<&str, (Self, BTreeMap<&str, u32>)> = BTreeMap::new();
+        for record in records {
+            let (stats, items) = stages.entry(&record.stage_code).or_insert_with(|| {
+                let stats = Self {
+                    stage_code: record.stage_code.clone(),
+                    sanity: Some(0),
+                    ..Default::default()
+                };
+                (stats, BTreeMap::new())
+            });
+            stats.runs += 1;
+            *stats.stars.entry(record.stars).or_default() += 1;
+            let cost = record
+                .ap_cost
+                .or_else(|| ap_costs.get(&record.stage_code).copied());
+            stats.sanity = stats.sanity.zip(cost).map(|(sum, cost)| sum + cost);
+            for (name, count) in &record.drops {
+                *items.entry(name).or_default() += count;
+            }
+        }
+
+        stages
+            .into_values()
+            .map(|(mut stats, items)| {
+                stats.items = items
+                    .into_iter()
+                    .map(|(name, count)| ItemDropStats {
+                        name: name.to_string(),
+                        count,
+                        per_run: count as f64 / stats.runs as f64,
+                        per_sanity: stats
+                            .sanity
+                            .filter(|s| *s > 0)
+                            .map(|s| count as f64 / s as f64),
+                    })
+                    .collect();
+                stats
+            })
+            .collect()
+    }
+}
+
+/// 每个关卡的每种物品一行
+pub fn stage_drop_stats_csv(stats: &[StageDropStats]) -> anyhow::Result<String> {
+    let mut s = String::from("stage,runs,sanity,item,count,per_run,per_sanity\n");
+    for stage in stats {
+        let sanity = stage.sanity.map(|s| s.to_string()).unwrap_or_default();
+        for item in &stage.items {
+            let per_sanity = item
+                .per_sanity
+                .map(|p| format!("{p:.4}"))
+                .unwrap_or_default();
+            writeln!(
+                s,
+                "{},{},{sanity},{},{},{:.4},{per_sanity}",
+                csv_field(&stage.stage_code),
+                stage.runs,
+                csv_field(&item.name),
+                item.count,
+                item.per_run
+            )?;
+        }
+    }
+    Ok(s)
+}
+
+/// 一个公招栏位的一次招募
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RecruitRecord {
+    pub time: DateTime<Local>,
+    /// 最后一次识别到的标签
+    pub tags: Vec<String>,
+    #[serde(default)]
+    pub special_tags: Vec<String>,
+    /// 保底星级
+    pub level: u8,
+    /// 选择的标签，未招募时为空
+    #[serde(default)]
+    pub selected_tags: Vec<String>,
+    #[serde(default)]
+    pub refreshes: u32,
+    /// 是否确认招募并消耗了招聘许可
+    #[serde(default)]
+    pub permit_used: bool,
+    /// 是否因招聘许可不足而未招募
+    #[serde(default)]
+    pub no_permit: bool,
+}
+
+#[derive(Debug, Default, PartialEq, Eq, Serialize)]
+pub struct RecruitStats {
+    pub attempts: u32,
+    pub refreshes: u32,
+    pub permits_used: u32,
+    /// 保底星级及对应的标签组数
+    pub levels: BTreeMap<u8, u32>,
+}
+
+impl RecruitStats {
+    fn add(&mut self, record: &RecruitRecord) {
+        self.attempts += 1;
+        self.refreshes += record.refreshes;
+        self.permits_used += u32::from(record.permit_used);
+        *self.levels.entry(record.level).or_default() += 1;
+    }
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct RecruitSummary {
+    pub total: RecruitStats,
+    /// 按本地日期统计
+    pub days: BTreeMap<NaiveDate, RecruitStats>,
+    /// 出现过的特殊标签及次数
+    pub special_tags: BTreeMap<String, u32>,
+}
+
+impl RecruitSummary {
+    pub fn collect<'a>(records: impl IntoIterator<Item = &'a RecruitRecord>) -> Self {
+        let mut summary = Self::default();
+        for record in records {
+            summary.total.add(record);
+            summary
+                .days
+                .entry(record.time.date_naive())
+                .or_default()
+                .add(record);
+            for tag in &record.special_tags {
+                *summary.special_tags.entry(tag.clone()).or_default() += 1;
+            }
+        }
+        summary
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+
+    use super::*;
+
+    fn record(stage: &str, ap_cost: Option<u32>, drops: &[(&str, u32)]) -> StageDropRecord {
+        StageDropRecord {
+            time: Local::now(),
+            stage_code: stage.to_string(),
+            stage_id: String::new(),
+            stars: 3,
+            ap_cost,
+            drops: drops.iter().map(|(n, c)| (n.to_string(), *c)).collect(),
+        }
+    }
+
+    #[test]
+    fn collect_stage_drops() {
+        let records = [
+            record("1-7", Some(6), &[("固源岩", 2)]),
+            record("1-7", Some(6), &[("固源岩", 1), ("源岩", 1)]),
+            record("CE-6", None, &[("龙门币", 10000)]),
+            record("S4-1", None, &[("糖", 1)]),
+        ];
+        let costs = HashMap::from([("CE-6".to_string(), 36)]);
+        let stats = StageDropStats::collect(&records, &costs);
+
+        assert_eq!(stats[0].stage_code, "1-7");
+        assert_eq!(stats[0].runs, 2);
+        assert_eq!(stats[0].stars[&3], 2);
+        assert_eq!(stats[0].sanity, Some(12));
+        assert_eq!(stats[0].items[0].name, "固源岩");
+        assert_eq!(stats[0].items[0].count, 3);
+        assert_eq!(stats[0].items[0].per_sanity, Some(0.25));
+        assert_eq!(stats[1].sanity, Some(36));
+        assert_eq!(stats[2].sanity, None);
+        assert_eq!(stats[2].items[0].per_sanity, None);
+
+        let csv = stage_drop_stats_csv(&stats).unwrap();
+        assert_eq!(csv.lines().nth(1), Some("1-7,2,12,固源岩,3,1.5000,0.2500"));
+        assert_eq!(csv.lines().last(), Some("S4-1,1,,糖,1,1.0000,"));
+
+        let records = [record("A,B", Some(1), &[("\"引号\"", 1)])];
+        let csv = stage_drop_stats_csv(&StageDropStats::collect(&records, &costs)).unwrap();
+        assert_eq!(
+            csv.lines().nth(1),
+            Some("\"A,B\",1,1,\"\"\"引号\"\"\",1,1.0000,1.0000")
+        );
+    }
+
+    #[test]
+    fn summarize_recruits() {
+        let day = |d: u32, level, refreshes, permit_used| RecruitRecord {
+            time: Local.with_ymd_and_hms(2026, 10, d, 12, 0, 0).unwrap(),
+            level,
+            refreshes,
+            permit_used,
+            ..Default::default()
+        };
+        let records = [
+            day(1, 5, 0, true),
+            day(1, 3, 2, true),
+            day(2, 4, 1, false),
+            day(2, 5, 0, true),
+        ];
+        let summary = RecruitSummary::collect(&records);
+        assert_eq!(summary.total.attempts, 4);
+        assert_eq!(summary.total.levels[&5], 2);
+        assert_eq!(summary.total.permits_used, 3);
+
+        let day2 = &summary.days[&NaiveDate::from_ymd_opt(2026, 10, 2).unwrap()];
+        assert_eq!(day2.refreshes, 1);
+        assert_eq!(day2.attempts, 2);
+        assert_eq!(summary.days.len(), 2);
+    }
+}
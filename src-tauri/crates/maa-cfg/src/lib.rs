@@ -1,21 +1,45 @@
 #![feature(if_let_guard)]
 #![deny(warnings)]
 
+pub mod backup;
+pub mod import;
+pub mod ledger;
+pub mod migration;
+pub mod params;
+pub mod profile;
+pub mod schedule;
 pub mod settings;
 pub mod task;
+pub mod tools;
+pub mod watch;
 
-use std::{env::current_dir, fs::create_dir_all, path::PathBuf, str::FromStr};
+use std::{
+    env::current_dir,
+    fs::create_dir_all,
+    path::{Path, PathBuf},
+    str::FromStr,
+    sync::Arc,
+};
 
-use anyhow::{Context, bail};
+use anyhow::{Context, bail, ensure};
 use dashmap::DashMap;
 use itertools::Itertools;
-use log::trace;
+use log::{info, trace, warn};
 use serde::Serialize;
 use strum::{Display, EnumString};
 pub use task::*;
-use tokio::{fs, join};
+use tokio::{
+    fs, join,
+    sync::{Mutex, OwnedMutexGuard},
+};
 
-use crate::settings::{AdbSettings, SettingType};
+use crate::{
+    backup::{BACKUP_DIR, Backup},
+    migration::{SCHEMA_VERSION, SCHEMA_VERSION_KEY},
+    schedule::ScheduleSettings,
+    settings::{AdbSettings, LanguageSettings, RemoteSettings, SettingType},
+    watch::ConflictError,
+};
 
 pub const CFG_DIR: &str = "config";
 pub const DEFAULT_CFG_PATH: &str = "default";
@@ -27,6 +51,16 @@ pub const TOOL_STORAGE: &str = "tool-storage";
 pub const CUSTOMS_CFG: &str = "customs";
 pub const CUSTOMS_STORAGE: &str = "custom-storage";
 pub const VERSION_JSON: &str = "versions";
+/// 自定义队列中的任务列表
+pub const CUSTOM_QUEUE_KEY: &str = "tasks";
+/// 加载到缓存中的配置
+pub const LOADED_CFGS: &[&str] = &[
+    DAILY_CFG,
+    TOOL_STORAGE,
+    SETTINGS_CFG,
+    CUSTOMS_CFG,
+    EXTRA_TASK_CFG,
+];
 
 /// 存放数据，而非gui配置本身
 #[derive(Debug, Display, EnumString)]
@@ -61,11 +95,19 @@ pub type ConfigValue = serde_json::Value;
 
 #[derive(Serialize, Clone)]
 pub struct Config {
+    #[serde(skip)]
+    name: String,
     #[serde(skip)]
     path: PathBuf,
     #[serde(flatten)]
     /// 避免async read file时跨线程持有mutex
     cfgs: DashMap<String, ConfigValue>,
+    /// 最后一次读写时磁盘内容的摘要，用于识别外部修改
+    #[serde(skip)]
+    known: DashMap<String, u64>,
+    /// 同一配置的读写依次进行，DashMap 的锁不能跨越 await
+    #[serde(skip)]
+    locks: DashMap<String, Arc<Mutex<()>>>,
 }
 
 macro_rules! get_cfg_path {
@@ -76,35 +118,53 @@ macro_rules! get_cfg_path {
 
 impl Config {
     pub async fn load(cfg_group: Option<String>) -> anyhow::Result<Self> {
-        let path = current_dir()
-            .context("cwd")?
-            .join(CFG_DIR)
-            .join(cfg_group.unwrap_or(DEFAULT_CFG_PATH.to_string()));
+        let name = cfg_group.unwrap_or(DEFAULT_CFG_PATH.to_string());
+        profile::validate_name(&name)?;
+        let path = cfg_root()?.join(&name);
 
         if let Err(e) = create_dir_all(&path)
             && !matches!(e.kind(), std::io::ErrorKind::AlreadyExists)
         {
             anyhow::bail!(e);
         };
+        let backup_dir = path.join(BACKUP_DIR);
         let (daily, tools, settings, customs, extra) = join!(
-            load_json_obj(get_cfg_path!(path, DAILY_CFG)),
-            load_json_obj(get_cfg_path!(path, TOOL_STORAGE)),
-            load_json_obj(get_cfg_path!(path, SETTINGS_CFG)),
-            load_json_obj(get_cfg_path!(path, CUSTOMS_CFG)),
-            load_json_obj(get_cfg_path!(path, EXTRA_TASK_CFG)),
+            load_cfg(get_cfg_path!(path, DAILY_CFG), &backup_dir),
+            load_cfg(get_cfg_path!(path, TOOL_STORAGE), &backup_dir),
+            load_cfg(get_cfg_path!(path, SETTINGS_CFG), &backup_dir),
+            load_cfg(get_cfg_path!(path, CUSTOMS_CFG), &backup_dir),
+            load_cfg(get_cfg_path!(path, EXTRA_TASK_CFG), &backup_dir),
         );
 
         let cfgs = DashMap::new();
-        cfgs.insert(DAILY_CFG.to_string(), daily.context("load daily")?);
-        cfgs.insert(TOOL_STORAGE.to_string(), tools.context("load tools")?);
-        cfgs.insert(SETTINGS_CFG.to_string(), settings.context("load settings")?);
-        cfgs.insert(CUSTOMS_CFG.to_string(), customs.context("load customs")?);
-        cfgs.insert(EXTRA_TASK_CFG.to_string(), extra.context("load extras")?);
+        let known = DashMap::new();
+        for (cfg, loaded) in [
+            (DAILY_CFG, daily.context("load daily")?),
+            (TOOL_STORAGE, tools.context("load tools")?),
+            (SETTINGS_CFG, settings.context("load settings")?),
+            (CUSTOMS_CFG, customs.context("load customs")?),
+            (EXTRA_TASK_CFG, extra.context("load extras")?),
+        ] {
+            let (value, hash) = loaded;
+            cfgs.insert(cfg.to_string(), value);
+            known.insert(cfg.to_string(), hash);
+        }
 
-        Ok(Self { path, cfgs })
+        Ok(Self {
+            name,
+            path,
+            cfgs,
+            known,
+            locks: DashMap::new(),
+        })
     }
 
-    /// 更新缓存并写入本地配置文件
+    /// 配置组名
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// 更新缓存并写入本地配置文件，任务参数会先经过校验
     ///
     /// # Parameter
     ///
@@ -118,17 +178,9 @@ impl Config {
     ) -> anyhow::Result<()> {
         trace!("cache config and write");
         match cfg_type {
-            ConfigType::Task(TaskType::Daily(t)) => {
-                let path = get_cfg_path!(self.path, DAILY_CFG);
-                self.set_and_write_impl(DAILY_CFG, path, t.to_string(), params)
-                    .await
-                    .context("write daily.json")
-            }
-            ConfigType::Task(TaskType::Extra(t)) => {
-                let path = get_cfg_path!(self.path, EXTRA_TASK_CFG);
-                self.set_and_write_impl(EXTRA_TASK_CFG, path, t.to_string(), params)
-                    .await
-                    .context("write extra_task.json")
+            // 以任务类型为 id 的默认任务
+            ConfigType::Task(t @ (TaskType::Daily(_) | TaskType::Extra(_))) => {
+                self.set_task_impl(t.name(), t, params).await
             }
             ConfigType::Task(TaskType::Custom(t)) => {
                 let path = get_cfg_path!(self.path, CUSTOMS_CFG);
@@ -149,6 +201,27 @@ impl Config {
                     .context("write custom-storage.json")
             }
             ConfigType::Settings(s) => {
+                match s {
+                    SettingType::Schedule => {
+                        let value = serde_json::to_value(&params.extra).context("serde params")?;
+                        serde_json::from_value::<ScheduleSettings>(value)
+                            .context("parse schedule settings")?
+                            .validate()?;
+                    }
+                    SettingType::Remote => {
+                        let value = serde_json::to_value(&params).context("serde params")?;
+                        serde_json::from_value::<RemoteSettings>(value)
+                            .context("parse remote settings")?
+                            .validate()?;
+                    }
+                    SettingType::Language => {
+                        let value = serde_json::to_value(&params).context("serde params")?;
+                        serde_json::from_value::<LanguageSettings>(value)
+                            .context("parse language settings")?
+                            .validate()?;
+                    }
+                    SettingType::Adb => {}
+                }
                 let path = get_cfg_path!(self.path, SETTINGS_CFG);
                 self.set_and_write_impl(SETTINGS_CFG, path, s.to_string(), params)
                     .await
@@ -165,36 +238,172 @@ impl Config {
         params: Parameters,
     ) -> anyhow::Result<()> {
         let value = serde_json::to_value(params).context("serde params to json value")?;
+        self.write_entry(cfg_type, path, key, Some(value)).await
+    }
+
+    /// 更新或删除（`value` 为空）一项配置并写入文件
+    async fn write_entry(
+        &self,
+        cfg_type: &str,
+        path: PathBuf,
+        key: String,
+        value: Option<ConfigValue>,
+    ) -> anyhow::Result<()> {
+        self.update_entry(cfg_type, path, key, |_| Ok(value)).await
+    }
+
+    /// 持有锁时按当前值计算新值并写入文件，`update` 返回空时删除该项
+    pub(crate) async fn update_entry(
+        &self,
+        cfg_type: &str,
+        path: PathBuf,
+        key: String,
+        update: impl FnOnce(Option<&ConfigValue>) -> anyhow::Result<Option<ConfigValue>>,
+    ) -> anyhow::Result<()> {
+        ensure!(key != SCHEMA_VERSION_KEY, "{key} is reserved");
+        let _lock = self.lock_cfg(cfg_type).await;
         let mut target = self
             .cfgs
-            .get_mut(cfg_type)
-            .ok_or_else(|| anyhow::anyhow!("no such cfg"))?;
+            .get(cfg_type)
+            .ok_or_else(|| anyhow::anyhow!("no such cfg"))?
+            .clone();
 
-        target
-            .as_object_mut()
-            .expect("must be object")
-            .insert(key, value);
+        // 外部修改尚未被重新加载，合并后再写入，避免覆盖
+        match self.external_change(cfg_type, &path).await {
+            Ok(Some(mut disk)) => {
+                migration::migrate(cfg_type, &mut disk).context("migrate changed config")?;
+                if disk.get(&key) != target.get(&key) {
+                    return Err(ConflictError {
+                        config: cfg_type.to_string(),
+                        key,
+                    }
+                    .into());
+                }
+                warn!("{cfg_type} was changed on disk, merged before writing");
+                target = disk;
+            }
+            Ok(None) => {}
+            Err(e) => warn!("check {path:?} before writing failed: {e:?}"),
+        }
 
-        let contents = serde_json::to_string_pretty(target.value()).context("serde json cache")?;
-        fs::write(&path, contents)
+        let obj = target.as_object_mut().expect("must be object");
+        match update(obj.get(&key))? {
+            Some(value) => obj.insert(key, value),
+            None => obj.remove(&key),
+        };
+
+        let contents = serde_json::to_string_pretty(&target).context("serde json cache")?;
+        self.backup_and_write(&path, contents).await?;
+        self.known
+            .insert(cfg_type.to_string(), watch::digest(&target));
+        self.cfgs.insert(cfg_type.to_string(), target);
+        Ok(())
+    }
+
+    /// 持有期间其他任务不能读写同一配置
+    pub(crate) async fn lock_cfg(&self, cfg_type: &str) -> OwnedMutexGuard<()> {
+        let lock = self.locks.entry(cfg_type.to_string()).or_default().clone();
+        lock.lock_owned().await
+    }
+
+    async fn backup_and_write(&self, path: &Path, contents: String) -> anyhow::Result<()> {
+        if let Err(e) = backup::backup(path, &self.path.join(BACKUP_DIR)).await {
+            warn!("backup {path:?} failed: {e:?}");
+        }
+        backup::write_atomic(path, contents)
             .await
             .with_context(|| format!("write config to {path:?}"))
     }
 
+    /// 列出当前配置组的备份，`config` 为空时列出全部
+    pub async fn list_backups(&self, config: Option<&str>) -> anyhow::Result<Vec<Backup>> {
+        backup::list(&self.path.join(BACKUP_DIR), config).await
+    }
+
+    /// 用指定备份覆盖对应的配置文件，覆盖前的内容同样会被备份
+    pub async fn restore_backup(&self, name: &str) -> anyhow::Result<()> {
+        let (backup, mut value) = backup::load(&self.path.join(BACKUP_DIR), name).await?;
+        migration::migrate(&backup.config, &mut value).context("migrate backup")?;
+        ensure!(
+            self.cfgs.contains_key(&backup.config),
+            "no such cfg: {}",
+            backup.config
+        );
+        let _lock = self.lock_cfg(&backup.config).await;
+
+        let path = self.path.join(format!("{}{CFG_SUFFIX}", backup.config));
+        let contents = serde_json::to_string_pretty(&value).context("serde backup")?;
+        self.backup_and_write(&path, contents).await?;
+        self.known
+            .insert(backup.config.clone(), watch::digest(&value));
+        self.cfgs.insert(backup.config.clone(), value);
+        info!("restored {} from {name}", backup.config);
+        Ok(())
+    }
+
     pub fn available_daily_tasks(&self) -> TaskQueue {
         self.available_tasks_impl(DAILY_CFG)
     }
 
+    /// 指定队列中启用的任务
+    pub fn task_queue(&self, kind: &QueueKind) -> anyhow::Result<TaskQueue> {
+        match kind {
+            QueueKind::Daily => Ok(self.available_tasks_impl(DAILY_CFG)),
+            QueueKind::Extra => Ok(self.available_tasks_impl(EXTRA_TASK_CFG)),
+            QueueKind::Customs => Ok(self
+                .enabled_task_entries(CUSTOMS_CFG)
+                .into_iter()
+                .filter(|(_, params)| params.get(CUSTOM_QUEUE_KEY).is_none())
+                .map(|(id, params)| queue_task(&id, params))
+                .collect()),
+            QueueKind::Named(name) => {
+                let queue = self
+                    .cfgs
+                    .get(CUSTOMS_CFG)
+                    .unwrap()
+                    .get(name)
+                    .cloned()
+                    .with_context(|| format!("no such custom queue: {name}"))?;
+                let tasks = queue
+                    .get(CUSTOM_QUEUE_KEY)
+                    .and_then(|t| t.as_array())
+                    .with_context(|| format!("{name} is not a custom queue"))?;
+                Ok(tasks
+                    .iter()
+                    .filter(|t| t["enable"].as_bool().unwrap_or(true))
+                    .cloned()
+                    .map(|params| queue_task(&ExtraTaskType::Custom.to_string(), params))
+                    .collect())
+            }
+        }
+    }
+
     fn available_tasks_impl(&self, key: &str) -> TaskQueue {
+        self.enabled_task_entries(key)
+            .into_iter()
+            .map(|(id, params)| queue_task(&id, params))
+            .collect()
+    }
+
+    /// 配置中的全部任务，以任务 id 为键，不含 `schema_version`
+    fn task_entries(&self, key: &str) -> Vec<(String, ConfigValue)> {
         self.cfgs
             .get(key)
             .unwrap()
             .as_object()
             .unwrap()
             .iter()
+            .filter(|(name, _)| *name != SCHEMA_VERSION_KEY)
+            .map(|(name, params)| (name.clone(), params.clone()))
+            .collect()
+    }
+
+    /// 已启用的任务，按 `index` 排序
+    fn enabled_task_entries(&self, key: &str) -> Vec<(String, ConfigValue)> {
+        self.task_entries(key)
+            .into_iter()
             .filter(|(_, params)| params["enable"].as_bool().unwrap_or_default())
             .sorted_by_key(|(_, params)| params["index"].as_i64().unwrap_or_default())
-            .map(|(name, params)| (name.to_string(), params.to_string()))
             .collect()
     }
 
@@ -203,10 +412,99 @@ impl Config {
             .get(SETTINGS_CFG)
             .unwrap()
             .get(SettingType::Adb.as_ref())
-            .map(|c| serde_json::from_str(c.as_str().unwrap_or_default()))
+            .map(|c| serde_json::from_value(c.clone()))
             .unwrap_or_else(|| Ok(AdbSettings::default()))
             .context("parse adb settings")
     }
+
+    pub fn schedule_config(&self) -> anyhow::Result<ScheduleSettings> {
+        self.cfgs
+            .get(SETTINGS_CFG)
+            .unwrap()
+            .get(SettingType::Schedule.as_ref())
+            .map(|c| serde_json::from_value(c.clone()))
+            .unwrap_or_else(|| Ok(ScheduleSettings::default()))
+            .context("parse schedule settings")
+    }
+
+    pub fn remote_config(&self) -> anyhow::Result<RemoteSettings> {
+        self.cfgs
+            .get(SETTINGS_CFG)
+            .unwrap()
+            .get(SettingType::Remote.as_ref())
+            .map(|c| serde_json::from_value(c.clone()))
+            .unwrap_or_else(|| Ok(RemoteSettings::default()))
+            .context("parse remote settings")
+    }
+
+    pub fn language_config(&self) -> anyhow::Result<LanguageSettings> {
+        self.cfgs
+            .get(SETTINGS_CFG)
+            .unwrap()
+            .get(SettingType::Language.as_ref())
+            .map(|c| serde_json::from_value(c.clone()))
+            .unwrap_or_else(|| Ok(LanguageSettings::default()))
+            .context("parse language settings")
+    }
+}
+
+/// 导出 csv 时转义包含逗号、引号或换行的字段
+pub fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+/// `config/` 目录，所有配置组的根目录
+pub fn cfg_root() -> anyhow::Result<PathBuf> {
+    Ok(current_dir().context("cwd")?.join(CFG_DIR))
+}
+
+/// 读取配置文件并迁移到当前版本，迁移前的文件会保留在备份目录
+///
+/// 同时返回磁盘内容的摘要
+async fn load_cfg(path: PathBuf, backup_dir: &Path) -> anyhow::Result<(ConfigValue, u64)> {
+    let config = path
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .unwrap_or_default();
+    let mut value = load_or_restore(&path, config, backup_dir).await?;
+    let mut hash = watch::digest(&value);
+
+    let version = migration::schema_version(&value);
+    if migration::migrate(config, &mut value)? {
+        backup::keep_pre_migration(&path, backup_dir, version)
+            .await
+            .context("backup before migration")?;
+        let contents = serde_json::to_string_pretty(&value).context("serde migrated config")?;
+        backup::write_atomic(&path, contents)
+            .await
+            .context("write migrated config")?;
+        hash = watch::digest(&value);
+        info!("migrated {config} from v{version} to v{SCHEMA_VERSION}");
+    }
+    Ok((value, hash))
+}
+
+/// 读取配置文件，解析失败时回退到最近的可用备份
+async fn load_or_restore(
+    path: &Path,
+    config: &str,
+    backup_dir: &Path,
+) -> anyhow::Result<ConfigValue> {
+    let err = match load_json_obj(path.to_path_buf()).await {
+        Ok(v) => return Ok(v),
+        Err(e) => e,
+    };
+    match backup::latest_valid(backup_dir, config).await? {
+        Some((backup, value)) => {
+            warn!("{path:?} is broken ({err}), restored from {}", backup.name);
+            Ok(value)
+        }
+        None => Err(err),
+    }
 }
 
 pub async fn load_json_obj(path: PathBuf) -> anyhow::Result<serde_json::Value> {
@@ -217,9 +515,123 @@ pub async fn load_json_obj(path: PathBuf) -> anyhow::Result<serde_json::Value> {
         }
         Err(e) => bail!("read file error: {}", e),
     };
+    if content.trim().is_empty() {
+        return Ok(serde_json::Value::Object(Default::default()));
+    }
     match serde_json::from_str(&content) {
         Ok(v) => Ok(v),
-        Err(e) if e.is_eof() => Ok(serde_json::Value::Object(Default::default())),
         Err(e) => bail!("parse json error: {}", e),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn empty_config() -> Config {
+        Config {
+            name: DEFAULT_CFG_PATH.to_string(),
+            path: PathBuf::new(),
+            cfgs: DashMap::new(),
+            known: DashMap::new(),
+            locks: DashMap::new(),
+        }
+    }
+
+    #[test]
+    fn repeated_tasks() {
+        let config = empty_config();
+        config.cfgs.insert(
+            DAILY_CFG.to_string(),
+            json!({
+                SCHEMA_VERSION_KEY: SCHEMA_VERSION,
+                "Fight": { "enable": true, "index": 1, "task_type": "Fight", "stage": "1-7" },
+                "Fight-2": { "enable": true, "index": 2, "task_type": "Fight", "stage": "CE-6" },
+                "StartUp": { "enable": true, "index": 0, "task_type": "StartUp" },
+            }),
+        );
+
+        let daily = config.available_daily_tasks();
+        let types: Vec<_> = daily.iter().map(|(t, _)| t.as_str()).collect();
+        assert_eq!(types, ["StartUp", "Fight", "Fight"]);
+        assert!(daily[2].1.contains("CE-6"));
+        assert!(!daily[2].1.contains(TASK_TYPE_KEY));
+    }
+
+    #[test]
+    fn single_step_round_trip() {
+        let params = serde_json::from_value(json!({
+            "enable": true,
+            "index": 0,
+            "type": "copilot",
+            "subtask": "stage",
+            "details": { "stage": "1-7" },
+        }))
+        .unwrap();
+        let task_type = TaskType::Extra(ExtraTaskType::SingleStep);
+        let (cfg, params) = task_entry(task_type, params).unwrap();
+        assert_eq!(cfg, EXTRA_TASK_CFG);
+        let config = empty_config();
+        config
+            .cfgs
+            .insert(EXTRA_TASK_CFG.to_string(), json!({ "SingleStep": params }));
+
+        let extra = config.task_queue(&QueueKind::Extra).unwrap();
+        assert_eq!(extra[0].0, "SingleStep");
+        let sent: ConfigValue = serde_json::from_str(&extra[0].1).unwrap();
+        assert_eq!(sent["type"], "copilot");
+        assert!(sent.get(TASK_TYPE_KEY).is_none());
+    }
+
+    #[test]
+    fn parse_config_types() {
+        assert!(matches!(
+            "Fight".parse(),
+            Ok(ConfigType::Task(TaskType::Daily(_)))
+        ));
+        assert!(matches!(
+            "Depot".parse(),
+            Ok(ConfigType::Task(TaskType::Extra(_)))
+        ));
+        assert!(matches!("Adb".parse(), Ok(ConfigType::Settings(_))));
+        assert!(matches!(
+            "custom:刷本".parse(),
+            Ok(ConfigType::Task(TaskType::Custom(name))) if name == "刷本"
+        ));
+        assert!("custom:".parse::<ConfigType>().is_err());
+        assert!("foo".parse::<ConfigType>().is_err());
+    }
+
+    #[test]
+    fn custom_queues() {
+        let config = empty_config();
+        config.cfgs.insert(
+            CUSTOMS_CFG.to_string(),
+            json!({
+                "b": { "enable": true, "index": 1, "task_names": ["b"] },
+                "a": { "enable": true, "index": 0, "task_type": "Depot" },
+                "off": { "enable": false },
+                "queue": { "enable": true, "tasks": [
+                    { "task_type": "Fight", "stage": "1-7" },
+                    { "task_type": "Mall", "enable": false },
+                    { "task_names": ["c"] },
+                ]},
+            }),
+        );
+
+        let customs = config.task_queue(&QueueKind::Customs).unwrap();
+        assert_eq!(customs[0].0, "Depot");
+        assert_eq!(customs[1].0, "b");
+        assert_eq!(customs.len(), 2);
+
+        let queue = config.task_queue(&"queue".parse().unwrap()).unwrap();
+        assert_eq!(queue[0].0, "Fight");
+        assert_eq!(queue[1].0, "Custom");
+        assert_eq!(queue.len(), 2);
+
+        assert!(config.task_queue(&"a".parse().unwrap()).is_err());
+        assert!(config.task_queue(&"missing".parse().unwrap()).is_err());
+    }
+}
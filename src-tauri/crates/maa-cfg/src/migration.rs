@@ -0,0 +1,176 @@
+//! 配置文件的版本迁移
+//!
+//! 每个配置文件顶层记录 `schema_version`，加载时按顺序执行 [`MIGRATIONS`]
+//! 中版本更高的迁移，直到 [`SCHEMA_VERSION`]
+
+use anyhow::{Context, bail};
+use log::warn;
+
+use crate::{
+    ConfigValue, DAILY_CFG, EXTRA_TASK_CFG, SETTINGS_CFG, TASK_TYPE_KEY, settings::SettingType,
+};
+
+pub const SCHEMA_VERSION_KEY: &str = "schema_version";
+/// 当前的配置版本，新增迁移时同步增加
+pub const SCHEMA_VERSION: u64 = 2;
+
+type JsonObject = serde_json::Map<String, ConfigValue>;
+
+pub struct Migration {
+    /// 迁移后的版本
+    pub to: u64,
+    pub description: &'static str,
+    /// 参数为配置名（如 `daily`）和配置文件内容
+    pub apply: fn(&str, &mut JsonObject) -> anyhow::Result<()>,
+}
+
+/// 按版本升序排列
+pub const MIGRATIONS: &[Migration] = &[
+    Migration {
+        to: 1,
+        description: "adb settings stored as object instead of json string",
+        apply: v1_adb_settings_object,
+    },
+    Migration {
+        to: 2,
+        description: "task entries keyed by id with a type field",
+        apply: v2_task_type_field,
+    },
+];
+
+/// 读取配置的版本，没有记录的视为 0
+pub fn schema_version(value: &ConfigValue) -> u64 {
+    value[SCHEMA_VERSION_KEY].as_u64().unwrap_or_default()
+}
+
+/// 将配置迁移到当前版本，返回是否发生了迁移
+///
+/// 空配置直接标记为当前版本，不视为迁移
+pub fn migrate(cfg: &str, value: &mut ConfigValue) -> anyhow::Result<bool> {
+    let version = schema_version(value);
+    let obj = value
+        .as_object_mut()
+        .with_context(|| format!("{cfg} is not a json object"))?;
+
+    if version > SCHEMA_VERSION {
+        bail!("{cfg} schema version {version} is newer than supported {SCHEMA_VERSION}");
+    }
+    if obj.is_empty() {
+        obj.insert(SCHEMA_VERSION_KEY.to_string(), SCHEMA_VERSION.into());
+        return Ok(false);
+    }
+    if version == SCHEMA_VERSION {
+        return Ok(false);
+    }
+
+    for migration in MIGRATIONS.iter().filter(|m| m.to > version) {
+        (migration.apply)(cfg, obj).with_context(|| {
+            format!(
+                "migrate {cfg} to v{}: {}",
+                migration.to, migration.description
+            )
+        })?;
+        obj.insert(SCHEMA_VERSION_KEY.to_string(), migration.to.into());
+    }
+    Ok(true)
+}
+
+/// v0 的 `settings.Adb` 是序列化后的json字符串
+fn v1_adb_settings_object(cfg: &str, obj: &mut JsonObject) -> anyhow::Result<()> {
+    if cfg != SETTINGS_CFG {
+        return Ok(());
+    }
+    let key = SettingType::Adb.as_ref();
+    if let Some(ConfigValue::String(s)) = obj.get(key) {
+        match serde_json::from_str::<ConfigValue>(s) {
+            Ok(v) if v.is_object() => {
+                obj.insert(key.to_string(), v);
+            }
+            _ => {
+                warn!("drop unparsable adb settings: {s}");
+                obj.remove(key);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// v1 的任务以任务类型为键，作为默认任务的 id 保留，并补充 `type`
+fn v2_task_type_field(cfg: &str, obj: &mut JsonObject) -> anyhow::Result<()> {
+    if cfg != DAILY_CFG && cfg != EXTRA_TASK_CFG {
+        return Ok(());
+    }
+    for (name, params) in obj.iter_mut() {
+        if let Some(params) = params.as_object_mut() {
+            params
+                .entry(TASK_TYPE_KEY)
+                .or_insert_with(|| name.clone().into());
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::DAILY_CFG;
+
+    #[test]
+    fn migrations_sorted() {
+        assert!(MIGRATIONS.windows(2).all(|w| w[0].to < w[1].to));
+        assert_eq!(MIGRATIONS.last().unwrap().to, SCHEMA_VERSION);
+    }
+
+    #[test]
+    fn empty_is_current() {
+        let mut value = json!({});
+        assert!(!migrate(DAILY_CFG, &mut value).unwrap());
+        assert_eq!(schema_version(&value), SCHEMA_VERSION);
+    }
+
+    #[test]
+    fn reject_newer() {
+        let mut value = json!({ SCHEMA_VERSION_KEY: SCHEMA_VERSION + 1 });
+        assert!(migrate(DAILY_CFG, &mut value).is_err());
+    }
+
+    #[test]
+    fn v1_adb_settings() {
+        let adb = json!({"path": "adb", "address": "127.0.0.1:5555", "extra": "None"});
+        let mut value = json!({ "Adb": adb.to_string() });
+        assert!(migrate(SETTINGS_CFG, &mut value).unwrap());
+        assert_eq!(value["Adb"], adb);
+        assert_eq!(schema_version(&value), SCHEMA_VERSION);
+
+        let mut value = json!({ "Adb": "{broken" });
+        migrate(SETTINGS_CFG, &mut value).unwrap();
+        assert!(value.get("Adb").is_none());
+
+        let mut value = json!({ SCHEMA_VERSION_KEY: 0, "Fight": { "enable": true } });
+        (MIGRATIONS[0].apply)(DAILY_CFG, value.as_object_mut().unwrap()).unwrap();
+        assert_eq!(value["Fight"], json!({ "enable": true }));
+    }
+
+    #[test]
+    fn v2_task_type() {
+        let mut value = json!({ SCHEMA_VERSION_KEY: 1, "Fight": { "enable": true } });
+        assert!(migrate(DAILY_CFG, &mut value).unwrap());
+        assert_eq!(
+            value["Fight"],
+            json!({ "enable": true, "task_type": "Fight" })
+        );
+        assert_eq!(value[SCHEMA_VERSION_KEY], json!(SCHEMA_VERSION));
+
+        let single_step = json!({ "enable": true, "type": "copilot", "subtask": "stage" });
+        let mut value = json!({ SCHEMA_VERSION_KEY: 1, "SingleStep": single_step });
+        migrate(EXTRA_TASK_CFG, &mut value).unwrap();
+        assert_eq!(value["SingleStep"][TASK_TYPE_KEY], "SingleStep");
+        assert_eq!(value["SingleStep"]["type"], "copilot");
+
+        let mut value = json!({ SCHEMA_VERSION_KEY: 1, "Adb": { "path": "adb" } });
+        migrate(SETTINGS_CFG, &mut value).unwrap();
+        assert!(value["Adb"].get(TASK_TYPE_KEY).is_none());
+    }
+}
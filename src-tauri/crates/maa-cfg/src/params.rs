@@ -0,0 +1,713 @@
+//! 各任务的参数模型，字段和默认值与 MaaCore 集成文档保持一致
+//!
+//! 写入配置前先按任务类型反序列化校验，未知字段和越界的值都会被拒绝
+
+use std::{collections::HashMap, fmt::Display};
+
+use itertools::Itertools;
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
+use thiserror::Error;
+
+use crate::{ConfigValue, DailyTaskType, ExtraTaskType, Parameters};
+
+/// gui自用的字段，不属于任务参数
+pub const GUI_KEYS: &[&str] = &["index"];
+
+const CLIENT_TYPES: &[&str] = &[
+    "", "Official", "Bilibili", "txwy", "YoStarEN", "YoStarJP", "YoStarKR",
+];
+const SERVERS: &[&str] = &["CN", "US", "JP", "KR"];
+const FACILITIES: &[&str] = &[
+    "Mfg",
+    "Trade",
+    "Power",
+    "Control",
+    "Reception",
+    "Office",
+    "Dorm",
+    "Processing",
+    "Training",
+];
+const DRONES: &[&str] = &[
+    "_NotUse",
+    "Money",
+    "SyntheticJade",
+    "CombatRecord",
+    "PureGold",
+    "OriginStone",
+    "Chip",
+];
+const ROGUELIKE_THEMES: &[&str] = &["Phantom", "Mizuki", "Sami", "Sarkaz", "JieGarden"];
+const RECLAMATION_THEMES: &[&str] = &["Fire", "Tales"];
+const SINGLE_STEP_SUBTASKS: &[&str] = &["stage", "start", "action"];
+
+#[derive(Debug, Error)]
+#[error("{field}: {reason}")]
+pub struct FieldError {
+    pub field: String,
+    pub reason: String,
+}
+
+impl FieldError {
+    fn new(field: impl Into<String>, reason: impl Display) -> Self {
+        Self {
+            field: field.into(),
+            reason: reason.to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Error)]
+#[error("invalid parameters for {task}: {}", .errors.iter().join("; "))]
+pub struct ParamsError {
+    pub task: String,
+    pub errors: Vec<FieldError>,
+}
+
+/// 反序列化无法表达的约束，比如取值范围和可选值
+trait Validate {
+    /// `enable` 为 false 时只检查取值，不检查必填项
+    fn validate(&self, enable: bool, errors: &mut Vec<FieldError>);
+}
+
+fn check_one_of(field: &str, value: &str, options: &[&str], errors: &mut Vec<FieldError>) {
+    if !options.contains(&value) {
+        errors.push(FieldError::new(
+            field,
+            format!("'{value}' is not one of {options:?}"),
+        ));
+    }
+}
+
+fn check_range<T: PartialOrd + Display>(
+    field: &str,
+    value: T,
+    min: T,
+    max: T,
+    errors: &mut Vec<FieldError>,
+) {
+    if value < min || value > max {
+        errors.push(FieldError::new(
+            field,
+            format!("{value} is out of range [{min}, {max}]"),
+        ));
+    }
+}
+
+fn check_required(field: &str, empty: bool, enable: bool, errors: &mut Vec<FieldError>) {
+    if enable && empty {
+        errors.push(FieldError::new(field, "required when task is enabled"));
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct StartUpParams {
+    pub client_type: String,
+    pub start_game_enabled: bool,
+    pub account_name: String,
+}
+
+impl Default for StartUpParams {
+    fn default() -> Self {
+        Self {
+            client_type: "Official".to_string(),
+            start_game_enabled: false,
+            account_name: String::new(),
+        }
+    }
+}
+
+impl Validate for StartUpParams {
+    fn validate(&self, _: bool, errors: &mut Vec<FieldError>) {
+        check_one_of("client_type", &self.client_type, CLIENT_TYPES, errors);
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct CloseDownParams {
+    pub client_type: String,
+}
+
+impl Default for CloseDownParams {
+    fn default() -> Self {
+        Self {
+            client_type: "Official".to_string(),
+        }
+    }
+}
+
+impl Validate for CloseDownParams {
+    fn validate(&self, _: bool, errors: &mut Vec<FieldError>) {
+        check_one_of("client_type", &self.client_type, CLIENT_TYPES, errors);
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct FightParams {
+    /// 为空时刷当前/上次关卡
+    pub stage: String,
+    pub medicine: u32,
+    pub expiring_medicine: u32,
+    pub stone: u32,
+    pub times: u32,
+    /// 连战次数，0 为自动，-1 为禁用切换
+    pub series: i32,
+    /// 指定掉落数量，`{ item_id: count }`
+    pub drops: HashMap<String, u32>,
+    pub report_to_penguin: bool,
+    pub penguin_id: String,
+    pub server: String,
+    pub client_type: String,
+    #[serde(rename = "DrGrandet")]
+    pub dr_grandet: bool,
+}
+
+impl Default for FightParams {
+    fn default() -> Self {
+        Self {
+            stage: String::new(),
+            medicine: 0,
+            expiring_medicine: 0,
+            stone: 0,
+            times: i32::MAX as u32,
+            series: 1,
+            drops: HashMap::new(),
+            report_to_penguin: false,
+            penguin_id: String::new(),
+            server: "CN".to_string(),
+            client_type: String::new(),
+            dr_grandet: false,
+        }
+    }
+}
+
+impl Validate for FightParams {
+    fn validate(&self, _: bool, errors: &mut Vec<FieldError>) {
+        check_range("series", self.series, -1, 6, errors);
+        check_range("times", self.times, 0, i32::MAX as u32, errors);
+        check_one_of("server", &self.server, SERVERS, errors);
+        check_one_of("client_type", &self.client_type, CLIENT_TYPES, errors);
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RecruitParams {
+    pub refresh: bool,
+    /// 会去点击标签的星级
+    pub select: Vec<u8>,
+    /// 会去点击确认的星级
+    pub confirm: Vec<u8>,
+    pub first_tags: Vec<String>,
+    pub extra_tags_mode: u8,
+    pub times: u32,
+    pub set_time: bool,
+    pub expedite: bool,
+    pub expedite_times: u32,
+    pub skip_robot: bool,
+    /// 各星级的招募时长（分钟），`{ "3": 540 }`
+    pub recruitment_time: HashMap<String, u32>,
+    pub report_to_penguin: bool,
+    pub penguin_id: String,
+    pub report_to_yituliu: bool,
+    pub yituliu_id: String,
+    pub server: String,
+}
+
+impl Default for RecruitParams {
+    fn default() -> Self {
+        Self {
+            refresh: false,
+            select: vec![4],
+            confirm: vec![3, 4],
+            first_tags: vec![],
+            extra_tags_mode: 0,
+            times: 0,
+            set_time: true,
+            expedite: false,
+            expedite_times: 0,
+            skip_robot: true,
+            recruitment_time: HashMap::new(),
+            report_to_penguin: false,
+            penguin_id: String::new(),
+            report_to_yituliu: false,
+            yituliu_id: String::new(),
+            server: "CN".to_string(),
+        }
+    }
+}
+
+impl Validate for RecruitParams {
+    fn validate(&self, _: bool, errors: &mut Vec<FieldError>) {
+        for (i, level) in self.select.iter().enumerate() {
+            check_range(&format!("select[{i}]"), *level, 1, 6, errors);
+        }
+        for (i, level) in self.confirm.iter().enumerate() {
+            check_range(&format!("confirm[{i}]"), *level, 1, 6, errors);
+        }
+        check_range("extra_tags_mode", self.extra_tags_mode, 0, 2, errors);
+        for (level, minutes) in &self.recruitment_time {
+            check_range(
+                &format!("recruitment_time.{level}"),
+                *minutes,
+                60,
+                540,
+                errors,
+            );
+        }
+        check_one_of("server", &self.server, SERVERS, errors);
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct InfrastParams {
+    /// 0 默认换班，10000 自定义换班，20000 一键轮换
+    pub mode: u32,
+    pub facility: Vec<String>,
+    pub drones: String,
+    /// 心情阈值
+    pub threshold: f64,
+    pub replenish: bool,
+    pub dorm_notstationed_enabled: bool,
+    pub dorm_trust_enabled: bool,
+    pub reception_message_board: bool,
+    pub reception_clue_exchange: bool,
+    /// 自定义换班的配置文件路径
+    pub filename: String,
+    pub plan_index: u32,
+}
+
+impl Default for InfrastParams {
+    fn default() -> Self {
+        Self {
+            mode: 0,
+            facility: FACILITIES[..7].iter().map(ToString::to_string).collect(),
+            drones: "_NotUse".to_string(),
+            threshold: 0.3,
+            replenish: false,
+            dorm_notstationed_enabled: false,
+            dorm_trust_enabled: false,
+            reception_message_board: true,
+            reception_clue_exchange: true,
+            filename: String::new(),
+            plan_index: 0,
+        }
+    }
+}
+
+impl Validate for InfrastParams {
+    fn validate(&self, enable: bool, errors: &mut Vec<FieldError>) {
+        if ![0, 10000, 20000].contains(&self.mode) {
+            errors.push(FieldError::new(
+                "mode",
+                format!("{} is not one of [0, 10000, 20000]", self.mode),
+            ));
+        }
+        check_required("facility", self.facility.is_empty(), enable, errors);
+        for (i, facility) in self.facility.iter().enumerate() {
+            check_one_of(&format!("facility[{i}]"), facility, FACILITIES, errors);
+        }
+        check_one_of("drones", &self.drones, DRONES, errors);
+        check_range("threshold", self.threshold, 0.0, 1.0, errors);
+        if self.mode == 10000 {
+            check_required("filename", self.filename.is_empty(), enable, errors);
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct MallParams {
+    pub shopping: bool,
+    pub buy_first: Vec<String>,
+    pub blacklist: Vec<String>,
+    pub force_shopping_if_credit_full: bool,
+    pub only_buy_discount: bool,
+    pub reserve_max_credit: bool,
+    pub credit_fight: bool,
+    pub visit_friends: bool,
+}
+
+impl Default for MallParams {
+    fn default() -> Self {
+        Self {
+            shopping: true,
+            buy_first: vec![],
+            blacklist: vec![],
+            force_shopping_if_credit_full: false,
+            only_buy_discount: false,
+            reserve_max_credit: false,
+            credit_fight: false,
+            visit_friends: true,
+        }
+    }
+}
+
+impl Validate for MallParams {
+    fn validate(&self, _: bool, errors: &mut Vec<FieldError>) {
+        for item in self.buy_first.iter().filter(|i| self.blacklist.contains(i)) {
+            errors.push(FieldError::new(
+                "buy_first",
+                format!("'{item}' is also in blacklist"),
+            ));
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct AwardParams {
+    pub award: bool,
+    pub mail: bool,
+    pub recruit: bool,
+    pub orundum: bool,
+    pub mining: bool,
+    pub specialaccess: bool,
+}
+
+impl Default for AwardParams {
+    fn default() -> Self {
+        Self {
+            award: true,
+            mail: false,
+            recruit: false,
+            orundum: false,
+            mining: false,
+            specialaccess: false,
+        }
+    }
+}
+
+impl Validate for AwardParams {
+    fn validate(&self, _: bool, _: &mut Vec<FieldError>) {}
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RoguelikeParams {
+    pub theme: String,
+    pub mode: u8,
+    pub squad: String,
+    pub roles: String,
+    pub core_char: String,
+    pub use_support: bool,
+    pub use_nonfriend_support: bool,
+    pub starts_count: u32,
+    pub difficulty: i32,
+    pub investment_enabled: bool,
+    pub investments_count: u32,
+    pub stop_when_investment_full: bool,
+    pub start_with_elite_two: bool,
+    pub only_start_with_elite_two: bool,
+    pub refresh_trader_with_dice: bool,
+    pub stop_at_final_boss: bool,
+    pub stop_at_max_level: bool,
+}
+
+impl Default for RoguelikeParams {
+    fn default() -> Self {
+        Self {
+            theme: "Phantom".to_string(),
+            mode: 0,
+            squad: String::new(),
+            roles: String::new(),
+            core_char: String::new(),
+            use_support: false,
+            use_nonfriend_support: false,
+            starts_count: i32::MAX as u32,
+            difficulty: 0,
+            investment_enabled: true,
+            investments_count: i32::MAX as u32,
+            stop_when_investment_full: false,
+            start_with_elite_two: false,
+            only_start_with_elite_two: false,
+            refresh_trader_with_dice: false,
+            stop_at_final_boss: false,
+            stop_at_max_level: false,
+        }
+    }
+}
+
+impl Validate for RoguelikeParams {
+    fn validate(&self, _: bool, errors: &mut Vec<FieldError>) {
+        check_one_of("theme", &self.theme, ROGUELIKE_THEMES, errors);
+        if ![0, 1, 4, 5, 6, 7].contains(&self.mode) {
+            errors.push(FieldError::new(
+                "mode",
+                format!("{} is not one of [0, 1, 4, 5, 6, 7]", self.mode),
+            ));
+        }
+        check_range("difficulty", self.difficulty, -1, 20, errors);
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ReclamationParams {
+    pub theme: String,
+    pub mode: u8,
+    pub tools_to_craft: Vec<String>,
+    pub increment_mode: u8,
+    pub num_craft_batches: u32,
+}
+
+impl Default for ReclamationParams {
+    fn default() -> Self {
+        Self {
+            theme: "Tales".to_string(),
+            mode: 0,
+            tools_to_craft: vec![],
+            increment_mode: 0,
+            num_craft_batches: 16,
+        }
+    }
+}
+
+impl Validate for ReclamationParams {
+    fn validate(&self, _: bool, errors: &mut Vec<FieldError>) {
+        check_one_of("theme", &self.theme, RECLAMATION_THEMES, errors);
+        check_range("mode", self.mode, 0, 1, errors);
+        check_range("increment_mode", self.increment_mode, 0, 1, errors);
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct CustomParams {
+    pub task_names: Vec<String>,
+}
+
+impl Validate for CustomParams {
+    fn validate(&self, enable: bool, errors: &mut Vec<FieldError>) {
+        check_required("task_names", self.task_names.is_empty(), enable, errors);
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct CopilotParams {
+    /// 作业 json 的路径
+    pub filename: String,
+    pub formation: bool,
+    pub formation_index: u32,
+    pub add_trust: bool,
+    pub ignore_requirements: bool,
+    pub support_unit_usage: u8,
+    pub support_unit_name: String,
+    pub loop_times: u32,
+    pub use_sanity_potion: bool,
+    pub need_navigate: bool,
+    pub navigate_name: String,
+    pub is_raid: bool,
+}
+
+impl Validate for CopilotParams {
+    fn validate(&self, enable: bool, errors: &mut Vec<FieldError>) {
+        check_required("filename", self.filename.is_empty(), enable, errors);
+        check_range("support_unit_usage", self.support_unit_usage, 0, 3, errors);
+        if self.need_navigate {
+            check_required(
+                "navigate_name",
+                self.navigate_name.is_empty(),
+                enable,
+                errors,
+            );
+        }
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SSSCopilotParams {
+    pub filename: String,
+    pub loop_times: u32,
+}
+
+impl Validate for SSSCopilotParams {
+    fn validate(&self, enable: bool, errors: &mut Vec<FieldError>) {
+        check_required("filename", self.filename.is_empty(), enable, errors);
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct EmptyParams {}
+
+impl Validate for EmptyParams {
+    fn validate(&self, _: bool, _: &mut Vec<FieldError>) {}
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SingleStepParams {
+    #[serde(rename = "type")]
+    pub type_: String,
+    pub subtask: String,
+    pub details: ConfigValue,
+}
+
+impl Default for SingleStepParams {
+    fn default() -> Self {
+        Self {
+            type_: "copilot".to_string(),
+            subtask: "stage".to_string(),
+            details: ConfigValue::Object(Default::default()),
+        }
+    }
+}
+
+impl Validate for SingleStepParams {
+    fn validate(&self, _: bool, errors: &mut Vec<FieldError>) {
+        check_one_of("type", &self.type_, &["copilot"], errors);
+        check_one_of("subtask", &self.subtask, SINGLE_STEP_SUBTASKS, errors);
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct VideoRecognitionParams {
+    pub filename: String,
+}
+
+impl Validate for VideoRecognitionParams {
+    fn validate(&self, enable: bool, errors: &mut Vec<FieldError>) {
+        check_required("filename", self.filename.is_empty(), enable, errors);
+    }
+}
+
+/// 按 `T` 校验参数，返回补全默认值后的参数
+fn validate_as<T>(task: &str, params: Parameters) -> Result<Parameters, ParamsError>
+where
+    T: DeserializeOwned + Serialize + Validate,
+{
+    let Parameters { enable, mut extra } = params;
+    let gui: HashMap<_, _> = GUI_KEYS
+        .iter()
+        .filter_map(|k| extra.remove_entry(*k))
+        .collect();
+
+    let value = ConfigValue::Object(extra.into_iter().collect());
+    let typed: T = serde_path_to_error::deserialize(value).map_err(|e| ParamsError {
+        task: task.to_string(),
+        errors: vec![FieldError::new(e.path().to_string(), e.inner())],
+    })?;
+
+    let mut errors = vec![];
+    typed.validate(enable, &mut errors);
+    if !errors.is_empty() {
+        return Err(ParamsError {
+            task: task.to_string(),
+            errors,
+        });
+    }
+
+    let ConfigValue::Object(map) = serde_json::to_value(typed).expect("params must be object")
+    else {
+        unreachable!("params must be object");
+    };
+    let mut extra: HashMap<_, _> = map.into_iter().collect();
+    extra.extend(gui);
+    Ok(Parameters { enable, extra })
+}
+
+impl DailyTaskType {
+    pub fn validate(&self, params: Parameters) -> Result<Parameters, ParamsError> {
+        let name = self.to_string();
+        match self {
+            DailyTaskType::StartUp => validate_as::<StartUpParams>(&name, params),
+            DailyTaskType::CloseDown => validate_as::<CloseDownParams>(&name, params),
+            DailyTaskType::Fight => validate_as::<FightParams>(&name, params),
+            DailyTaskType::Recruit => validate_as::<RecruitParams>(&name, params),
+            DailyTaskType::Infrast => validate_as::<InfrastParams>(&name, params),
+            DailyTaskType::Mall => validate_as::<MallParams>(&name, params),
+            DailyTaskType::Award => validate_as::<AwardParams>(&name, params),
+            DailyTaskType::Roguelike => validate_as::<RoguelikeParams>(&name, params),
+            DailyTaskType::Reclamation => validate_as::<ReclamationParams>(&name, params),
+        }
+    }
+}
+
+impl ExtraTaskType {
+    pub fn validate(&self, params: Parameters) -> Result<Parameters, ParamsError> {
+        let name = self.to_string();
+        match self {
+            ExtraTaskType::Custom => validate_as::<CustomParams>(&name, params),
+            ExtraTaskType::Copilot => validate_as::<CopilotParams>(&name, params),
+            ExtraTaskType::SSSCopilot => validate_as::<SSSCopilotParams>(&name, params),
+            ExtraTaskType::Depot | ExtraTaskType::OperBox => {
+                validate_as::<EmptyParams>(&name, params)
+            }
+            ExtraTaskType::SingleStep => validate_as::<SingleStepParams>(&name, params),
+            ExtraTaskType::VideoRecognition => validate_as::<VideoRecognitionParams>(&name, params),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn params(value: ConfigValue) -> Parameters {
+        serde_json::from_value(value).unwrap()
+    }
+
+    #[test]
+    fn fill_defaults_and_keep_gui_keys() {
+        let p = DailyTaskType::Fight
+            .validate(params(json!({"enable": true, "index": 2, "stage": "1-7"})))
+            .unwrap();
+        assert!(p.enable);
+        assert_eq!(p.extra["index"], json!(2));
+        assert_eq!(p.extra["stage"], json!("1-7"));
+        assert_eq!(p.extra["medicine"], json!(0));
+        assert_eq!(p.extra["series"], json!(1));
+    }
+
+    #[test]
+    fn reject_type_error_with_field() {
+        let e = DailyTaskType::Fight
+            .validate(params(json!({"enable": true, "medicine": "a lot"})))
+            .unwrap_err();
+        assert_eq!(e.errors[0].field, "medicine");
+    }
+
+    #[test]
+    fn reject_unknown_field() {
+        let e = DailyTaskType::Mall
+            .validate(params(json!({"enable": true, "buy_frist": []})))
+            .unwrap_err();
+        assert_eq!(e.errors.len(), 1);
+    }
+
+    #[test]
+    fn reject_out_of_range() {
+        let e = DailyTaskType::Infrast
+            .validate(params(json!({
+                "enable": true,
+                "threshold": 1.5,
+                "facility": ["Mfg", "Pool"],
+            })))
+            .unwrap_err();
+        let fields = e.errors.iter().map(|e| e.field.as_str()).collect_vec();
+        assert_eq!(fields, ["facility[1]", "threshold"]);
+    }
+
+    #[test]
+    fn required_only_when_enabled() {
+        assert!(
+            ExtraTaskType::Copilot
+                .validate(params(json!({"enable": false})))
+                .is_ok()
+        );
+        let e = ExtraTaskType::Copilot
+            .validate(params(json!({"enable": true})))
+            .unwrap_err();
+        assert_eq!(e.errors[0].field, "filename");
+    }
+}
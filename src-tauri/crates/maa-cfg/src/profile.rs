@@ -0,0 +1,167 @@
+//! 配置组管理，每个配置组对应 `config/<name>/` 目录
+
+use std::path::{Component, Path, PathBuf};
+
+use anyhow::{Context, bail, ensure};
+use log::{info, trace};
+use serde::{Deserialize, Serialize};
+use tokio::fs;
+
+use crate::{CFG_SUFFIX, DEFAULT_CFG_PATH, backup::write_atomic, cfg_root, load_json_obj};
+
+/// 记录当前激活的配置组，位于 `config/profiles.json`
+pub const PROFILES_CFG: &str = "profiles";
+
+const INVALID_CHARS: &[char] = &['<', '>', ':', '"', '/', '\\', '|', '?', '*'];
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Profiles {
+    pub active: String,
+    pub profiles: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct ActiveProfile {
+    active: String,
+}
+
+fn profiles_path() -> anyhow::Result<PathBuf> {
+    Ok(cfg_root()?.join(constcat::concat!(PROFILES_CFG, CFG_SUFFIX)))
+}
+
+fn profile_path(name: &str) -> anyhow::Result<PathBuf> {
+    validate_name(name)?;
+    Ok(cfg_root()?.join(name))
+}
+
+/// 配置组名会直接作为目录名，禁止路径分隔符和保留字符
+pub fn validate_name(name: &str) -> anyhow::Result<()> {
+    ensure!(!name.trim().is_empty(), "profile name is empty");
+    ensure!(
+        !name.contains(INVALID_CHARS) && !name.starts_with('.'),
+        "invalid profile name: {name}"
+    );
+    let mut components = Path::new(name).components();
+    ensure!(
+        matches!(components.next(), Some(Component::Normal(_))) && components.next().is_none(),
+        "invalid profile name: {name}"
+    );
+    Ok(())
+}
+
+/// 读取当前激活的配置组，未设置时为 `default`
+pub async fn active_profile() -> anyhow::Result<String> {
+    let value = load_json_obj(profiles_path()?)
+        .await
+        .context("load profiles")?;
+    Ok(value["active"]
+        .as_str()
+        .unwrap_or(DEFAULT_CFG_PATH)
+        .to_string())
+}
+
+pub async fn set_active_profile(name: &str) -> anyhow::Result<()> {
+    ensure!(
+        profile_path(name)?.is_dir(),
+        "profile '{name}' does not exist"
+    );
+    let contents = serde_json::to_string_pretty(&ActiveProfile {
+        active: name.to_string(),
+    })
+    .context("serde active profile")?;
+    write_atomic(&profiles_path()?, contents)
+        .await
+        .context("write profiles")
+}
+
+pub async fn list_profiles() -> anyhow::Result<Profiles> {
+    let root = cfg_root()?;
+    fs::create_dir_all(&root).await.context("create cfg dir")?;
+
+    let mut profiles = vec![];
+    let mut entries = fs::read_dir(&root).await.context("read cfg dir")?;
+    while let Some(entry) = entries.next_entry().await.context("read cfg entry")? {
+        if entry.file_type().await.context("file type")?.is_dir()
+            && let Some(name) = entry.file_name().to_str()
+            && validate_name(name).is_ok()
+        {
+            profiles.push(name.to_string());
+        }
+    }
+    profiles.sort();
+
+    Ok(Profiles {
+        active: active_profile().await?,
+        profiles,
+    })
+}
+
+pub async fn create_profile(name: &str) -> anyhow::Result<()> {
+    let path = profile_path(name)?;
+    ensure!(!path.exists(), "profile '{name}' already exists");
+    trace!("create profile {name}");
+    fs::create_dir_all(&path)
+        .await
+        .with_context(|| format!("create {path:?}"))
+}
+
+pub async fn clone_profile(from: &str, to: &str) -> anyhow::Result<()> {
+    let src = profile_path(from)?;
+    let dst = profile_path(to)?;
+    ensure!(src.is_dir(), "profile '{from}' does not exist");
+    ensure!(!dst.exists(), "profile '{to}' already exists");
+    trace!("clone profile {from} -> {to}");
+    copy_dir(&src, &dst).await
+}
+
+/// 重命名配置组，如果是当前激活的配置组则同步更新激活记录
+pub async fn rename_profile(from: &str, to: &str) -> anyhow::Result<()> {
+    let src = profile_path(from)?;
+    let dst = profile_path(to)?;
+    ensure!(src.is_dir(), "profile '{from}' does not exist");
+    ensure!(!dst.exists(), "profile '{to}' already exists");
+    trace!("rename profile {from} -> {to}");
+    fs::rename(&src, &dst)
+        .await
+        .with_context(|| format!("rename {src:?} to {dst:?}"))?;
+    if active_profile().await? == from {
+        set_active_profile(to).await?;
+    }
+    Ok(())
+}
+
+/// 删除配置组，不允许删除当前激活的配置组
+pub async fn delete_profile(name: &str) -> anyhow::Result<()> {
+    let path = profile_path(name)?;
+    ensure!(path.is_dir(), "profile '{name}' does not exist");
+    if active_profile().await? == name {
+        bail!("cannot delete active profile '{name}'");
+    }
+    info!("delete profile {name}");
+    fs::remove_dir_all(&path)
+        .await
+        .with_context(|| format!("remove {path:?}"))
+}
+
+async fn copy_dir(src: &Path, dst: &Path) -> anyhow::Result<()> {
+    let mut stack = vec![(src.to_path_buf(), dst.to_path_buf())];
+    while let Some((src, dst)) = stack.pop() {
+        fs::create_dir_all(&dst)
+            .await
+            .with_context(|| format!("create {dst:?}"))?;
+        let mut entries = fs::read_dir(&src)
+            .await
+            .with_context(|| format!("read {src:?}"))?;
+        while let Some(entry) = entries.next_entry().await.context("read entry")? {
+            let target = dst.join(entry.file_name());
+            if entry.file_type().await.context("file type")?.is_dir() {
+                stack.push((entry.path(), target));
+            } else {
+                fs::copy(entry.path(), &target)
+                    .await
+                    .with_context(|| format!("copy to {target:?}"))?;
+            }
+        }
+    }
+    Ok(())
+}
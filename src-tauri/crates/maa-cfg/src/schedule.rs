@@ -0,0 +1,197 @@
+//! 定时运行的触发器，保存在 `settings.json` 的 `Schedule` 中
+
+use std::str::FromStr;
+
+use anyhow::Context;
+use chrono::{DateTime, Local, NaiveTime, TimeZone};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::{
+    CFG_SUFFIX, QueueKind, SETTINGS_CFG, cfg_root, load_json_obj, migration, profile,
+    settings::SettingType,
+};
+
+#[derive(Debug, Default, Deserialize, Serialize, Clone)]
+pub struct ScheduleSettings {
+    /// 总开关
+    #[serde(default)]
+    pub enable: bool,
+    #[serde(default)]
+    pub triggers: Vec<Trigger>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct Trigger {
+    #[serde(default = "default_enable")]
+    pub enable: bool,
+    /// 要运行的队列，见 [`QueueKind`]
+    #[serde(default = "default_queue")]
+    pub queue: String,
+    #[serde(flatten)]
+    pub when: TriggerTime,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+pub enum TriggerTime {
+    /// 每天的固定时间，如 `04:05`
+    Daily {
+        #[serde(serialize_with = "ser_time", deserialize_with = "de_time")]
+        time: NaiveTime,
+    },
+    /// cron 表达式，`分 时 日 月 周`，也可以带秒和年
+    Cron { expr: String },
+}
+
+fn default_enable() -> bool {
+    true
+}
+
+fn default_queue() -> String {
+    QueueKind::Daily.to_string()
+}
+
+const TIME_FORMAT: &str = "%H:%M";
+
+fn ser_time<S: Serializer>(time: &NaiveTime, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(&time.format(TIME_FORMAT).to_string())
+}
+
+fn de_time<'de, D: Deserializer<'de>>(deserializer: D) -> Result<NaiveTime, D::Error> {
+    let s = String::deserialize(deserializer)?;
+    NaiveTime::parse_from_str(&s, TIME_FORMAT)
+        .or_else(|_| NaiveTime::parse_from_str(&s, "%H:%M:%S"))
+        .map_err(serde::de::Error::custom)
+}
+
+impl ScheduleSettings {
+    /// 检查所有 cron 表达式能否解析
+    pub fn validate(&self) -> anyhow::Result<()> {
+        for trigger in &self.triggers {
+            if let TriggerTime::Cron { expr } = &trigger.when {
+                cron_schedule(expr)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// 只读取配置组 `settings.json` 中的定时设置，迁移仅在内存中进行，不会写回
+pub async fn load_schedule(profile: &str) -> anyhow::Result<ScheduleSettings> {
+    profile::validate_name(profile)?;
+    let path = cfg_root()?
+        .join(profile)
+        .join(constcat::concat!(SETTINGS_CFG, CFG_SUFFIX));
+    let mut settings = load_json_obj(path).await.context("load settings")?;
+    migration::migrate(SETTINGS_CFG, &mut settings)?;
+    settings
+        .get(SettingType::Schedule.as_ref())
+        .map(|c| serde_json::from_value(c.clone()))
+        .unwrap_or_else(|| Ok(ScheduleSettings::default()))
+        .context("parse schedule settings")
+}
+
+impl TriggerTime {
+    /// `(after, until]` 内的触发时间，按时间升序
+    pub fn fire_times(
+        &self,
+        after: DateTime<Local>,
+        until: DateTime<Local>,
+    ) -> anyhow::Result<Vec<DateTime<Local>>> {
+        Ok(self.fire_iter(after)?.take_while(|t| *t <= until).collect())
+    }
+
+    /// 下一次触发时间
+    pub fn next_fire(&self, after: DateTime<Local>) -> anyhow::Result<Option<DateTime<Local>>> {
+        Ok(self.fire_iter(after)?.next())
+    }
+
+    /// `after` 之后的触发时间，按需逐个计算
+    fn fire_iter(
+        &self,
+        after: DateTime<Local>,
+    ) -> anyhow::Result<Box<dyn Iterator<Item = DateTime<Local>>>> {
+        match self {
+            TriggerTime::Daily { time } => {
+                let time = *time;
+                Ok(Box::new(
+                    after
+                        .date_naive()
+                        .iter_days()
+                        .filter_map(move |d| {
+                            Local.from_local_datetime(&d.and_time(time)).earliest()
+                        })
+                        .filter(move |t| after < *t),
+                ))
+            }
+            TriggerTime::Cron { expr } => Ok(Box::new(cron_schedule(expr)?.after_owned(after))),
+        }
+    }
+}
+
+/// 5段的表达式补上秒
+fn cron_schedule(expr: &str) -> anyhow::Result<cron::Schedule> {
+    let expr = match expr.split_whitespace().count() {
+        5 => format!("0 {expr}"),
+        _ => expr.to_string(),
+    };
+    cron::Schedule::from_str(&expr).with_context(|| format!("invalid cron expression: {expr}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn local(s: &str) -> DateTime<Local> {
+        let t = chrono::NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M").unwrap();
+        Local.from_local_datetime(&t).earliest().unwrap()
+    }
+
+    #[test]
+    fn daily_and_cron() {
+        let settings: ScheduleSettings = serde_json::from_value(json!({
+            "enable": true,
+            "triggers": [
+                { "kind": "daily", "time": "04:05" },
+                { "kind": "cron", "expr": "0 */8 * * *", "queue": "extra" },
+            ]
+        }))
+        .unwrap();
+        settings.validate().unwrap();
+        assert_eq!(settings.triggers[0].queue, "daily");
+
+        let after = local("2025-01-01 03:00");
+        let times = settings.triggers[0]
+            .when
+            .fire_times(after, local("2025-01-03 04:00"))
+            .unwrap();
+        assert_eq!(times, [
+            local("2025-01-01 04:05"),
+            local("2025-01-02 04:05")
+        ]);
+
+        let times = settings.triggers[1]
+            .when
+            .fire_times(after, local("2025-01-01 17:00"))
+            .unwrap();
+        assert_eq!(times, [
+            local("2025-01-01 08:00"),
+            local("2025-01-01 16:00")
+        ]);
+
+        let every_second = TriggerTime::Cron {
+            expr: "* * * * * *".to_string(),
+        };
+        assert_eq!(
+            every_second.next_fire(after).unwrap(),
+            Some(after + chrono::TimeDelta::seconds(1))
+        );
+
+        let broken = TriggerTime::Cron {
+            expr: "every day".to_string(),
+        };
+        assert!(broken.next_fire(after).is_err());
+    }
+}
@@ -1,11 +1,19 @@
-use std::path::PathBuf;
+use std::{net::SocketAddr, path::PathBuf};
 
+use anyhow::{Context, anyhow, ensure};
+use maa_callback::i18n::Language;
 use serde::{Deserialize, Serialize};
 use strum::{AsRefStr, Display, EnumString};
 
 #[derive(Debug, EnumString, Display, AsRefStr)]
 pub enum SettingType {
     Adb,
+    /// 定时运行，见 [`crate::schedule::ScheduleSettings`]
+    Schedule,
+    /// 远程控制接口，见 [`RemoteSettings`]
+    Remote,
+    /// 回调消息的语言，见 [`LanguageSettings`]
+    Language,
 }
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
@@ -25,6 +33,88 @@ impl Default for AdbSettings {
     }
 }
 
+/// 本地 HTTP/WebSocket 控制接口，默认关闭，修改后重启生效
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct RemoteSettings {
+    #[serde(default)]
+    pub enable: bool,
+    /// 监听地址，默认只允许本机访问
+    #[serde(default = "default_remote_address")]
+    pub address: String,
+    /// 请求需要携带的 token
+    #[serde(default)]
+    pub token: String,
+}
+
+pub const DEFAULT_REMOTE_ADDRESS: &str = "127.0.0.1:19420";
+
+fn default_remote_address() -> String {
+    DEFAULT_REMOTE_ADDRESS.to_string()
+}
+
+impl Default for RemoteSettings {
+    fn default() -> Self {
+        Self {
+            enable: false,
+            address: default_remote_address(),
+            token: String::new(),
+        }
+    }
+}
+
+impl RemoteSettings {
+    pub fn socket_addr(&self) -> anyhow::Result<SocketAddr> {
+        self.address
+            .parse()
+            .with_context(|| format!("invalid address {}", self.address))
+    }
+
+    /// 开启时必须设置 token
+    pub fn validate(&self) -> anyhow::Result<()> {
+        self.socket_addr()?;
+        ensure!(
+            !self.enable || !self.token.is_empty(),
+            "token is required to enable remote control"
+        );
+        Ok(())
+    }
+}
+
+/// 回调消息的语言，修改后立即生效
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct LanguageSettings {
+    /// `zh-CN`、`en-US` 或 `ja-JP`
+    #[serde(default = "default_language")]
+    pub language: String,
+}
+
+pub const DEFAULT_LANGUAGE: &str = "zh-CN";
+
+fn default_language() -> String {
+    DEFAULT_LANGUAGE.to_string()
+}
+
+impl Default for LanguageSettings {
+    fn default() -> Self {
+        Self {
+            language: default_language(),
+        }
+    }
+}
+
+impl LanguageSettings {
+    /// 解析为回调消息的语言
+    pub fn language(&self) -> anyhow::Result<Language> {
+        self.language
+            .parse()
+            .map_err(|_| anyhow!("unknown language: {}", self.language))
+    }
+
+    pub fn validate(&self) -> anyhow::Result<()> {
+        self.language().map(drop)
+    }
+}
+
 #[derive(Debug, Default, Deserialize, Serialize, AsRefStr, Clone)]
 pub enum ExtraAdb {
     #[default]
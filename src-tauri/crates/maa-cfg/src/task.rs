@@ -1,12 +1,52 @@
 use std::{collections::HashMap, str::FromStr};
 
+use anyhow::{Context, anyhow, bail, ensure};
 use serde::{Deserialize, Serialize};
 use strum::{Display, EnumString};
 
-use crate::ConfigValue;
+use crate::{CFG_SUFFIX, Config, ConfigValue, DAILY_CFG, EXTRA_TASK_CFG};
 
+/// 任务参数中的任务类型，同一类型的任务可以有多个，以 id 区分
+///
+/// 不使用 `type`，避免与 `SingleStep` 自身的 `type` 参数冲突
+pub const TASK_TYPE_KEY: &str = "task_type";
+
+/// 自定义任务的配置名前缀，如 `custom:刷本` 对应 `customs.json` 中的 `刷本`
+pub const CUSTOM_TASK_PREFIX: &str = "custom:";
+
+/// (任务类型, 参数)，同一类型可以重复出现
 pub type TaskQueue = Vec<(String, String)>;
 
+/// 可执行的任务队列
+#[derive(Debug, Clone, PartialEq, Eq, Display)]
+pub enum QueueKind {
+    /// `daily.json` 中启用的任务
+    #[strum(serialize = "daily")]
+    Daily,
+    /// `extra-task.json` 中启用的任务
+    #[strum(serialize = "extra")]
+    Extra,
+    /// `customs.json` 中启用的自定义任务
+    #[strum(serialize = "customs")]
+    Customs,
+    /// `customs.json` 中带有 `tasks` 列表的自定义队列
+    #[strum(to_string = "{0}")]
+    Named(String),
+}
+
+impl FromStr for QueueKind {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "daily" => Ok(Self::Daily),
+            "extra" | "extra-task" => Ok(Self::Extra),
+            "customs" => Ok(Self::Customs),
+            _ => Ok(Self::Named(s.to_string())),
+        }
+    }
+}
+
 /// 既可以作为gui的配置项，也可以作为执行任务的参数名
 #[derive(Debug, Display)]
 pub enum TaskType {
@@ -22,7 +62,21 @@ impl FromStr for TaskType {
         match s {
             s if let Ok(t) = s.parse() => Ok(Self::Daily(t)),
             s if let Ok(t) = s.parse() => Ok(Self::Extra(t)),
-            _ => Ok(Self::Custom(s.to_string())),
+            s if let Some(name) = s.strip_prefix(CUSTOM_TASK_PREFIX).filter(|n| !n.is_empty()) => {
+                Ok(Self::Custom(name.to_string()))
+            }
+            _ => Err(()),
+        }
+    }
+}
+
+impl TaskType {
+    /// MaaCore 的任务类型名，也是该类型默认任务的 id
+    pub fn name(&self) -> String {
+        match self {
+            TaskType::Daily(t) => t.to_string(),
+            TaskType::Extra(t) => t.to_string(),
+            TaskType::Custom(t) => t.clone(),
         }
     }
 }
@@ -57,3 +111,113 @@ pub struct Parameters {
     #[serde(flatten)]
     pub extra: HashMap<String, ConfigValue>,
 }
+
+impl Config {
+    /// 新增一个任务，返回生成的 id，如 `Fight-2`
+    pub async fn add_task(
+        &self,
+        task_type: TaskType,
+        params: Parameters,
+    ) -> anyhow::Result<String> {
+        let name = task_type.name();
+        let id = (1..)
+            .map(|n| match n {
+                1 => name.clone(),
+                n => format!("{name}-{n}"),
+            })
+            .find(|id| self.task_cfg_of(id).is_none())
+            .expect("infinite ids");
+        self.set_task_impl(id.clone(), task_type, params).await?;
+        Ok(id)
+    }
+
+    /// 更新指定 id 的任务，参数中没有 `task_type` 时沿用原来的类型
+    pub async fn set_task(&self, id: &str, mut params: Parameters) -> anyhow::Result<()> {
+        let type_ = match params.extra.remove(TASK_TYPE_KEY) {
+            Some(ConfigValue::String(t)) => t,
+            Some(v) => bail!("invalid task type: {v}"),
+            None => self
+                .task_type_of(id)
+                .with_context(|| format!("no such task: {id}"))?,
+        };
+        let task_type = type_
+            .parse()
+            .map_err(|_| anyhow!("unknown task type: {type_}"))?;
+        self.set_task_impl(id.to_string(), task_type, params).await
+    }
+
+    /// 删除指定 id 的任务
+    pub async fn remove_task(&self, id: &str) -> anyhow::Result<()> {
+        let cfg = self
+            .task_cfg_of(id)
+            .with_context(|| format!("no such task: {id}"))?;
+        let path = self.path.join(format!("{cfg}{CFG_SUFFIX}"));
+        self.write_entry(cfg, path, id.to_string(), None)
+            .await
+            .with_context(|| format!("write {cfg}.json"))
+    }
+
+    pub(crate) async fn set_task_impl(
+        &self,
+        id: String,
+        task_type: TaskType,
+        params: Parameters,
+    ) -> anyhow::Result<()> {
+        let (cfg, params) = task_entry(task_type, params)?;
+        if let Some(other) = self.task_cfg_of(&id) {
+            ensure!(other == cfg, "task id {id} is already used in {other}");
+        }
+
+        let path = self.path.join(format!("{cfg}{CFG_SUFFIX}"));
+        self.set_and_write_impl(cfg, path, id, params)
+            .await
+            .with_context(|| format!("write {cfg}.json"))
+    }
+
+    /// 任务所在的配置
+    fn task_cfg_of(&self, id: &str) -> Option<&'static str> {
+        [DAILY_CFG, EXTRA_TASK_CFG]
+            .into_iter()
+            .find(|cfg| self.cfgs.get(*cfg).unwrap().get(id).is_some())
+    }
+
+    fn task_type_of(&self, id: &str) -> Option<String> {
+        let cfg = self.task_cfg_of(id)?;
+        self.cfgs.get(cfg).unwrap()[id][TASK_TYPE_KEY]
+            .as_str()
+            .map(str::to_string)
+    }
+}
+
+/// 校验任务参数并记录任务类型，返回任务所在的配置和要保存的参数
+pub(crate) fn task_entry(
+    task_type: TaskType,
+    mut params: Parameters,
+) -> anyhow::Result<(&'static str, Parameters)> {
+    let name = task_type.name();
+    if let Some(type_) = params.extra.remove(TASK_TYPE_KEY) {
+        ensure!(
+            type_ == name.as_str(),
+            "task type {type_} mismatches {name}"
+        );
+    }
+    let (cfg, mut params) = match task_type {
+        TaskType::Daily(t) => (DAILY_CFG, t.validate(params)?),
+        TaskType::Extra(t) => (EXTRA_TASK_CFG, t.validate(params)?),
+        TaskType::Custom(t) => bail!("unknown task type: {t}"),
+    };
+    params.extra.insert(TASK_TYPE_KEY.to_string(), name.into());
+    Ok((cfg, params))
+}
+
+/// 拆出任务类型，其余作为任务参数
+///
+/// 没有类型的以 `fallback` 作为类型，与迁移时一致
+pub(crate) fn queue_task(fallback: &str, mut params: ConfigValue) -> (String, String) {
+    let type_ = params
+        .as_object_mut()
+        .and_then(|p| p.remove(TASK_TYPE_KEY))
+        .and_then(|t| t.as_str().map(str::to_string))
+        .unwrap_or_else(|| fallback.to_string());
+    (type_, params.to_string())
+}
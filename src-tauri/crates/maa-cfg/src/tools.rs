@@ -0,0 +1,95 @@
+//! 小工具识别结果的存储，每种结果按时间保存在 `tool-storage.json` 中
+
+use anyhow::Context;
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+
+use crate::{CFG_SUFFIX, Config, ConfigValue, TOOL_STORAGE};
+
+/// 每种结果保留的历史记录数
+pub const TOOL_HISTORY_LIMIT: usize = 30;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ToolRecord {
+    pub time: DateTime<Local>,
+    pub data: ConfigValue,
+}
+
+/// `tool-storage.json` 中一种结果的内容，按时间从旧到新排列
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct ToolHistory {
+    history: Vec<ToolRecord>,
+}
+
+impl ToolHistory {
+    fn push(&mut self, record: ToolRecord) {
+        self.history.push(record);
+        let overflow = self.history.len().saturating_sub(TOOL_HISTORY_LIMIT);
+        self.history.drain(..overflow);
+    }
+}
+
+impl Config {
+    /// 追加一条识别结果，超出 [`TOOL_HISTORY_LIMIT`] 的旧记录会被丢弃
+    pub async fn record_tool(&self, tool: &str, data: ConfigValue) -> anyhow::Result<()> {
+        let path = self.path.join(format!("{TOOL_STORAGE}{CFG_SUFFIX}"));
+        // 读取和写入在同一把锁内，同时保存的结果不会互相覆盖
+        self.update_entry(TOOL_STORAGE, path, tool.to_string(), |current| {
+            let mut tools = parse_history(tool, current)?;
+            tools.push(ToolRecord {
+                time: Local::now(),
+                data,
+            });
+            serde_json::to_value(tools)
+                .context("serde tool history")
+                .map(Some)
+        })
+        .await
+        .with_context(|| format!("record {tool}"))
+    }
+
+    /// 某种结果的全部记录，从旧到新
+    pub fn tool_history(&self, tool: &str) -> anyhow::Result<Vec<ToolRecord>> {
+        Ok(self.tool_history_impl(tool)?.history)
+    }
+
+    /// 某种结果的最新记录
+    pub fn latest_tool(&self, tool: &str) -> anyhow::Result<Option<ToolRecord>> {
+        Ok(self.tool_history_impl(tool)?.history.pop())
+    }
+
+    fn tool_history_impl(&self, tool: &str) -> anyhow::Result<ToolHistory> {
+        parse_history(tool, self.cfgs.get(TOOL_STORAGE).unwrap().get(tool))
+    }
+}
+
+fn parse_history(tool: &str, value: Option<&ConfigValue>) -> anyhow::Result<ToolHistory> {
+    value
+        .map(|v| serde_json::from_value(v.clone()))
+        .unwrap_or_else(|| Ok(ToolHistory::default()))
+        .with_context(|| format!("parse {tool} in {TOOL_STORAGE}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn limit_history() {
+        let mut tools = ToolHistory::default();
+        for i in 0..TOOL_HISTORY_LIMIT + 2 {
+            tools.push(ToolRecord {
+                time: Local::now(),
+                data: json!({ "i": i }),
+            });
+        }
+        assert_eq!(tools.history.len(), TOOL_HISTORY_LIMIT);
+        assert_eq!(tools.history[0].data["i"], 2);
+        assert_eq!(
+            tools.history.last().unwrap().data["i"],
+            TOOL_HISTORY_LIMIT + 1
+        );
+    }
+}
@@ -0,0 +1,141 @@
+//! 监听配置目录，外部修改配置文件后重新加载
+//!
+//! 每个配置记录最后一次读写时磁盘内容的摘要，摘要相同的变更视为自身写入并忽略
+
+use std::{
+    hash::{DefaultHasher, Hash, Hasher},
+    path::Path,
+};
+
+use anyhow::{Context, ensure};
+use log::{trace, warn};
+pub use notify::RecommendedWatcher;
+use notify::{EventKind, RecursiveMode, Watcher};
+use serde::Serialize;
+
+use crate::{CFG_SUFFIX, Config, ConfigValue, LOADED_CFGS, cfg_root, load_json_obj, migration};
+
+/// 外部修改的配置文件
+#[derive(Debug, Clone, Serialize)]
+pub struct ConfigChange {
+    /// 配置组名
+    pub profile: String,
+    /// 配置名，如 `daily`
+    pub config: String,
+}
+
+impl ConfigChange {
+    /// 只接受 `config/<profile>/<cfg>.json`，忽略备份和临时文件
+    fn from_path(root: &Path, path: &Path) -> Option<Self> {
+        let relative = path.strip_prefix(root).ok()?;
+        let mut components = relative.iter().map(|c| c.to_str());
+        let (Some(Some(profile)), Some(Some(file)), None) =
+            (components.next(), components.next(), components.next())
+        else {
+            return None;
+        };
+        let config = file.strip_suffix(CFG_SUFFIX)?;
+        LOADED_CFGS.contains(&config).then(|| Self {
+            profile: profile.to_string(),
+            config: config.to_string(),
+        })
+    }
+}
+
+/// GUI和磁盘同时修改了同一项配置
+#[derive(Debug, thiserror::Error)]
+#[error("{config}.{key} was changed both on disk and in gui")]
+pub struct ConflictError {
+    pub config: String,
+    pub key: String,
+}
+
+/// 监听全部配置组，返回的 watcher 被 drop 后停止监听
+pub fn watch<F>(on_change: F) -> anyhow::Result<RecommendedWatcher>
+where
+    F: Fn(ConfigChange) + Send + 'static,
+{
+    let root = cfg_root()?;
+    let watch_root = root.clone();
+    let mut watcher = notify::recommended_watcher(move |event: notify::Result<notify::Event>| {
+        let event = match event {
+            Ok(e) => e,
+            Err(e) => {
+                warn!("config watcher error: {e}");
+                return;
+            }
+        };
+        if !matches!(event.kind, EventKind::Create(_) | EventKind::Modify(_)) {
+            return;
+        }
+        event
+            .paths
+            .iter()
+            .filter_map(|p| ConfigChange::from_path(&watch_root, p))
+            .for_each(|change| {
+                trace!("config file changed: {change:?}");
+                on_change(change)
+            });
+    })
+    .context("create config watcher")?;
+    watcher
+        .watch(&root, RecursiveMode::Recursive)
+        .with_context(|| format!("watch {root:?}"))?;
+    Ok(watcher)
+}
+
+/// 配置内容的摘要，与格式无关
+pub(crate) fn digest(value: &ConfigValue) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    value.to_string().hash(&mut hasher);
+    hasher.finish()
+}
+
+impl Config {
+    /// 重新读取被外部修改的配置文件，返回缓存是否更新
+    pub async fn reload_file(&self, config: &str) -> anyhow::Result<bool> {
+        let path = self.path.join(format!("{config}{CFG_SUFFIX}"));
+        ensure!(self.cfgs.contains_key(config), "no such cfg: {config}");
+        // 避免与 set_and_write 交错
+        let _lock = self.lock_cfg(config).await;
+        let Some(mut value) = self.external_change(config, &path).await? else {
+            return Ok(false);
+        };
+        let hash = digest(&value);
+        migration::migrate(config, &mut value).context("migrate changed config")?;
+        self.cfgs.insert(config.to_string(), value);
+        self.known.insert(config.to_string(), hash);
+        Ok(true)
+    }
+
+    /// 磁盘内容与最后一次读写时不同则返回磁盘内容
+    pub(crate) async fn external_change(
+        &self,
+        config: &str,
+        path: &Path,
+    ) -> anyhow::Result<Option<ConfigValue>> {
+        let value = load_json_obj(path.to_path_buf())
+            .await
+            .with_context(|| format!("load {path:?}"))?;
+        let known = self.known.get(config).map(|h| *h);
+        Ok((known != Some(digest(&value))).then_some(value))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::*;
+
+    #[test]
+    fn change_path() {
+        let root = PathBuf::from("config");
+        let change = ConfigChange::from_path(&root, &root.join("default").join("daily.json"));
+        assert_eq!(change.unwrap().config, "daily");
+
+        assert!(ConfigChange::from_path(&root, &root.join("profiles.json")).is_none());
+        assert!(ConfigChange::from_path(&root, &root.join("default/daily.json.tmp")).is_none());
+        assert!(ConfigChange::from_path(&root, &root.join("default/backups/daily.json")).is_none());
+    }
+}
@@ -0,0 +1,99 @@
+//! MaaCore 操作的抽象，[`MaaSysBackend`] 调用真实的 MaaCore，
+//! `fake` 模块按脚本发送回调，用于在没有 MaaCore 和模拟器的环境下测试
+
+#[cfg(any(test, feature = "fake-backend"))]
+pub mod fake;
+
+use std::{env::current_exe, ffi::c_void};
+
+use anyhow::{Context, anyhow};
+use log::trace;
+use maa_cfg::settings::{AdbSettings, ExtraAdb};
+use maa_sys::{Assistant, binding::AsstApiCallback};
+use maa_types::primitive::AsstTaskId;
+
+use crate::core::{LOAD_CORE, MAA_CORE};
+
+/// MaaCore 的全局操作
+pub trait CoreBackend: Sync {
+    type Assistant: AssistantHandle;
+
+    /// 加载 MaaCore，已加载时不重复加载
+    fn load_core(&self) -> anyhow::Result<()>;
+    fn load_resource(&self) -> anyhow::Result<()>;
+    /// 全局的连接额外配置，对之后的连接生效
+    fn set_connection_extras(&self, extra: &ExtraAdb) -> anyhow::Result<()>;
+    /// `arg` 会原样传给回调
+    fn create(&self, callback: AsstApiCallback, arg: *mut c_void) -> Self::Assistant;
+}
+
+/// 单个 Assistant 实例的操作，drop 时销毁实例，之后不会再有回调
+pub trait AssistantHandle {
+    /// 阻塞直到连接完成
+    fn connect(&self, adb_cfg: &AdbSettings) -> anyhow::Result<()>;
+    fn append_task(&self, name: &str, params: &str) -> anyhow::Result<AsstTaskId>;
+    fn start(&self) -> anyhow::Result<()>;
+    fn stop(&self) -> anyhow::Result<()>;
+    fn running(&self) -> bool;
+}
+
+/// 通过 maa-sys 调用 MaaCore
+pub struct MaaSysBackend;
+
+impl CoreBackend for MaaSysBackend {
+    type Assistant = Assistant;
+
+    fn load_core(&self) -> anyhow::Result<()> {
+        LOAD_CORE
+            .get_or_try_init(|| {
+                trace!("load MaaCore");
+                maa_sys::binding::load(MAA_CORE)
+                    .map_err(|e| anyhow!(e))
+                    .context("load core")
+            })
+            .context("once load core")?;
+        Ok(())
+    }
+
+    fn load_resource(&self) -> anyhow::Result<()> {
+        let exe_path = current_exe().context("get exe path")?;
+        Assistant::load_resource(exe_path.parent().unwrap()).context("load resource")
+    }
+
+    fn set_connection_extras(&self, extra: &ExtraAdb) -> anyhow::Result<()> {
+        crate::core::set_connection_extras(extra)
+    }
+
+    fn create(&self, callback: AsstApiCallback, arg: *mut c_void) -> Self::Assistant {
+        Assistant::new(callback, Some(arg))
+    }
+}
+
+impl AssistantHandle for Assistant {
+    fn connect(&self, adb_cfg: &AdbSettings) -> anyhow::Result<()> {
+        self.async_connect(
+            adb_cfg.path.as_os_str(),
+            adb_cfg.address.as_str(),
+            adb_cfg.extra.as_ref(),
+            true,
+        )
+        .context("connect")?;
+        Ok(())
+    }
+
+    fn append_task(&self, name: &str, params: &str) -> anyhow::Result<AsstTaskId> {
+        Assistant::append_task(self, name, params).with_context(|| format!("append task {name}"))
+    }
+
+    fn start(&self) -> anyhow::Result<()> {
+        Assistant::start(self).context("start")
+    }
+
+    fn stop(&self) -> anyhow::Result<()> {
+        Assistant::stop(self).context("stop")
+    }
+
+    fn running(&self) -> bool {
+        Assistant::running(self)
+    }
+}
@@ -0,0 +1,159 @@
+//! 按脚本发送回调的假后端
+
+use std::{
+    ffi::{CString, c_void},
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicBool, Ordering},
+    },
+    thread::{self, JoinHandle},
+    time::Duration,
+};
+
+use anyhow::{anyhow, bail};
+use maa_callback::callback::AsstMsgCode;
+use maa_cfg::settings::{AdbSettings, ExtraAdb};
+use maa_sys::binding::AsstApiCallback;
+use maa_types::primitive::AsstTaskId;
+use serde_json::{Value, json};
+
+use super::{AssistantHandle, CoreBackend};
+
+/// 发送回调的间隔
+const EVENT_INTERVAL: Duration = Duration::from_millis(5);
+
+#[derive(Debug, Clone, Default)]
+pub struct Script {
+    /// 连接失败时的错误信息
+    pub connect_error: Option<String>,
+    /// 追加时失败的任务类型
+    pub rejected_tasks: Vec<String>,
+    /// `start` 后依次发送的回调
+    pub events: Vec<(AsstMsgCode, Value)>,
+    /// 回调发送完后保持运行，直到 `stop`
+    pub hold: bool,
+}
+
+impl Script {
+    /// 依次完成给定的任务链，最后发送全部完成
+    pub fn complete(tasks: &[&str]) -> Self {
+        let mut events = vec![];
+        for (id, task) in tasks.iter().enumerate() {
+            let info = json!({ "taskchain": task, "taskid": id + 1 });
+            events.push((AsstMsgCode::TaskChainStart, info.clone()));
+            events.push((AsstMsgCode::TaskChainCompleted, info));
+        }
+        events.push((AsstMsgCode::AllTasksCompleted, json!({})));
+        Self {
+            events,
+            ..Default::default()
+        }
+    }
+}
+
+pub struct FakeBackend {
+    pub script: Script,
+}
+
+impl CoreBackend for FakeBackend {
+    type Assistant = FakeAssistant;
+
+    fn load_core(&self) -> anyhow::Result<()> {
+        Ok(())
+    }
+
+    fn load_resource(&self) -> anyhow::Result<()> {
+        Ok(())
+    }
+
+    fn set_connection_extras(&self, _: &ExtraAdb) -> anyhow::Result<()> {
+        Ok(())
+    }
+
+    fn create(&self, callback: AsstApiCallback, arg: *mut c_void) -> Self::Assistant {
+        FakeAssistant {
+            script: self.script.clone(),
+            callback,
+            arg: arg as usize,
+            running: Arc::new(AtomicBool::new(false)),
+            stopped: Arc::new(AtomicBool::new(false)),
+            worker: Mutex::new(None),
+        }
+    }
+}
+
+pub struct FakeAssistant {
+    script: Script,
+    callback: AsstApiCallback,
+    /// 回调的 `arg`，以整数保存以便传给发送线程
+    arg: usize,
+    running: Arc<AtomicBool>,
+    stopped: Arc<AtomicBool>,
+    worker: Mutex<Option<JoinHandle<()>>>,
+}
+
+impl AssistantHandle for FakeAssistant {
+    fn connect(&self, _: &AdbSettings) -> anyhow::Result<()> {
+        match &self.script.connect_error {
+            Some(e) => Err(anyhow!(e.clone())),
+            None => Ok(()),
+        }
+    }
+
+    fn append_task(&self, name: &str, _: &str) -> anyhow::Result<AsstTaskId> {
+        if self.script.rejected_tasks.iter().any(|t| t == name) {
+            bail!("append task {name}: rejected by script");
+        }
+        Ok(1)
+    }
+
+    fn start(&self) -> anyhow::Result<()> {
+        self.running.store(true, Ordering::Release);
+        let events = self.script.events.clone();
+        let hold = self.script.hold;
+        let (callback, arg) = (self.callback, self.arg);
+        let (running, stopped) = (self.running.clone(), self.stopped.clone());
+
+        let worker = thread::spawn(move || {
+            for (code, details) in events {
+                if stopped.load(Ordering::Acquire) {
+                    break;
+                }
+                emit(callback, arg, code, &details);
+                thread::sleep(EVENT_INTERVAL);
+            }
+            while hold && !stopped.load(Ordering::Acquire) {
+                thread::sleep(EVENT_INTERVAL);
+            }
+            running.store(false, Ordering::Release);
+        });
+        *self.worker.lock().unwrap() = Some(worker);
+        Ok(())
+    }
+
+    fn stop(&self) -> anyhow::Result<()> {
+        self.stopped.store(true, Ordering::Release);
+        Ok(())
+    }
+
+    fn running(&self) -> bool {
+        self.running.load(Ordering::Acquire)
+    }
+}
+
+impl Drop for FakeAssistant {
+    fn drop(&mut self) {
+        self.stopped.store(true, Ordering::Release);
+        if let Some(worker) = self.worker.lock().unwrap().take() {
+            let _ = worker.join();
+        }
+    }
+}
+
+fn emit(callback: AsstApiCallback, arg: usize, code: AsstMsgCode, details: &Value) {
+    let Some(callback) = callback else {
+        return;
+    };
+    let details = CString::new(details.to_string()).expect("no nul in json");
+    unsafe { callback(code as i32, details.as_ptr(), arg as *mut c_void) };
+}
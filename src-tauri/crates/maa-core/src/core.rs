@@ -1,74 +1,112 @@
 use std::{
-    env::{
-        consts::{DLL_PREFIX, DLL_SUFFIX},
-        current_exe,
-    },
-    ffi::c_void,
-    sync::OnceLock,
+    env::consts::{DLL_PREFIX, DLL_SUFFIX},
+    sync::{Mutex, OnceLock, PoisonError},
     time::Duration,
 };
 
 use anyhow::{Context, anyhow, bail};
 use crossbeam_channel::select;
-use log::{debug, trace};
-use maa_callback::callback::STOP_CHAN;
+use log::{debug, trace, warn};
 use maa_cfg::{
     settings::{AdbSettings, ExtraAdb},
     task::TaskQueue,
 };
 use maa_sys::Assistant;
 
-const MAA_CORE: &str = constcat::concat!(DLL_PREFIX, "MaaCore", DLL_SUFFIX);
-static LOAD_CORE: OnceLock<()> = OnceLock::new();
+use crate::{
+    backend::{AssistantHandle, CoreBackend, MaaSysBackend},
+    registry::{self, RunGuard},
+    state::{self, RunState},
+};
+
+pub const MAA_CORE: &str = constcat::concat!(DLL_PREFIX, "MaaCore", DLL_SUFFIX);
+pub(crate) static LOAD_CORE: OnceLock<()> = OnceLock::new();
+/// 资源是否已加载，资源由所有实例共享
+static RESOURCE_LOADED: Mutex<bool> = Mutex::new(false);
+/// 连接的额外配置是全局的，设置和连接需要在实例之间互斥
+static CONNECT_LOCK: Mutex<()> = Mutex::new(());
+/// 运行结束后等待回调处理完的最长时间
+const FLUSH_TIMEOUT: Duration = Duration::from_secs(10);
 
-/// run all tasks with given queue and callback
+/// run all tasks with given queue and callback as instance `id`.
+///
+/// the callback receives `RunContext` of this run as `arg`.
+/// the run state of the instance ends with `Idle` or `Failed`.
 pub fn run_core(
+    id: &str,
     tasks: TaskQueue,
     callback: maa_sys::binding::AsstApiCallback,
     adb_cfg: AdbSettings,
-    arg: Option<*mut c_void>,
 ) -> anyhow::Result<()> {
-    LOAD_CORE
-        .get_or_try_init(|| {
-            trace!("load MaaCore");
-            maa_sys::binding::load(MAA_CORE)
-                .map_err(|e| anyhow!(e))
-                .context("load core")
-        })
-        .context("once load core")?;
+    run_with(&MaaSysBackend, id, tasks, callback, adb_cfg)
+}
 
-    trace!("load resource");
-    let exe_path = current_exe().context("get exe path")?;
-    Assistant::load_resource(exe_path.parent().unwrap()).context("load resource")?;
+/// same as `run_core`, but with the given backend.
+pub fn run_with<B: CoreBackend>(
+    backend: &B,
+    id: &str,
+    tasks: TaskQueue,
+    callback: maa_sys::binding::AsstApiCallback,
+    adb_cfg: AdbSettings,
+) -> anyhow::Result<()> {
+    // 必须比 assistant 后释放
+    let run = registry::register(id, &tasks)?;
+    let result = run_core_impl(backend, &run, tasks, callback, adb_cfg);
+    // 实例已销毁，不会再有新的回调，处理完已收到的回调再切换最终状态
+    if !maa_callback::callback::flush(FLUSH_TIMEOUT) {
+        warn!("callback messages of {id} are not fully handled");
+    }
+    let next = match &result {
+        Ok(()) => RunState::Idle,
+        Err(e) => RunState::Failed(format!("{e:#}")),
+    };
+    if let Err(e) = state::transit(id, next) {
+        warn!("{e}");
+    }
+    result
+}
+
+fn run_core_impl<B: CoreBackend>(
+    backend: &B,
+    run: &RunGuard,
+    tasks: TaskQueue,
+    callback: maa_sys::binding::AsstApiCallback,
+    adb_cfg: AdbSettings,
+) -> anyhow::Result<()> {
+    backend.load_core()?;
+    load_resource(backend)?;
 
-    let assistant = Assistant::new(callback, arg);
+    let assistant = backend.create(callback, run.ctx.as_arg());
 
-    trace!("set ex adb");
-    set_connection_extras(&adb_cfg.extra).context("set connection extras")?;
+    if !transit_unless_stopped(run, RunState::Connecting)? {
+        return Ok(());
+    }
+    {
+        let _connecting = CONNECT_LOCK.lock().unwrap_or_else(PoisonError::into_inner);
+        trace!("set ex adb");
+        backend
+            .set_connection_extras(&adb_cfg.extra)
+            .context("set connection extras")?;
 
-    trace!("connect adb");
-    assistant
-        .async_connect(
-            adb_cfg.path.as_os_str(),
-            adb_cfg.address.as_str(),
-            adb_cfg.extra.as_ref(),
-            true,
-        )
-        .context("connect")?;
+        trace!("connect adb");
+        assistant.connect(&adb_cfg)?;
+    }
 
     trace!("append tasks");
     for (name, params) in tasks {
-        let id = assistant
-            .append_task(name.as_str(), params.as_str())
-            .with_context(|| format!("append task {name}"))?;
+        let id = assistant.append_task(name.as_str(), params.as_str())?;
         debug!("append task '{}' (id: {})", name, id);
     }
 
+    // 先切换状态，避免覆盖任务链开始的回调设置的状态
+    if !transit_unless_stopped(run, RunState::Running(None))? {
+        return Ok(());
+    }
     trace!("run tasks");
-    assistant.start().context("start")?;
+    assistant.start()?;
     loop {
         select! {
-            recv(STOP_CHAN.rx) -> msg => {
+            recv(run.ctx.stop_rx()) -> msg => {
                 if let Err(e) = msg {
                     bail!(e);
                 }
@@ -80,7 +118,36 @@ pub fn run_core(
         }
     }
     trace!("stop asst");
-    assistant.stop().context("stop")
+    state::transit(&run.ctx.id, RunState::Stopping)?;
+    assistant.stop()
+}
+
+/// 启动过程中切换状态，已收到停止请求时返回 false 以提前结束
+fn transit_unless_stopped(run: &RunGuard, next: RunState) -> anyhow::Result<bool> {
+    match state::transit(&run.ctx.id, next) {
+        Ok(()) => Ok(true),
+        Err(_) if stop_requested(run) => Ok(false),
+        Err(e) => Err(e),
+    }
+}
+
+fn stop_requested(run: &RunGuard) -> bool {
+    matches!(state::get(&run.ctx.id), RunState::Stopping)
+}
+
+/// 其他实例运行时资源正在使用，不重新加载
+fn load_resource<B: CoreBackend>(backend: &B) -> anyhow::Result<()> {
+    let mut loaded = RESOURCE_LOADED
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner);
+    if *loaded && registry::count() > 1 {
+        trace!("resource is used by other instances, skip loading");
+        return Ok(());
+    }
+    trace!("load resource");
+    backend.load_resource()?;
+    *loaded = true;
+    Ok(())
 }
 
 pub fn reload_core() -> anyhow::Result<()> {
@@ -93,10 +160,10 @@ pub fn reload_core() -> anyhow::Result<()> {
 }
 
 #[cfg(feature = "tauri-handle")]
-pub fn run_core_tauri(tasks: TaskQueue, adb_cfg: AdbSettings) -> anyhow::Result<()> {
+pub fn run_core_tauri(id: &str, tasks: TaskQueue, adb_cfg: AdbSettings) -> anyhow::Result<()> {
     use maa_callback::callback::default_callback_log;
 
-    run_core(tasks, Some(default_callback_log), adb_cfg, None)
+    run_core(id, tasks, Some(default_callback_log), adb_cfg)
 }
 
 pub fn set_connection_extras(ex: &ExtraAdb) -> anyhow::Result<()> {
@@ -108,3 +175,106 @@ pub fn set_connection_extras(ex: &ExtraAdb) -> anyhow::Result<()> {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::{thread, time::Instant};
+
+    use maa_callback::callback::{AsstMsgCode, default_callback_log};
+    use serde_json::json;
+
+    use super::*;
+    use crate::backend::fake::{FakeBackend, Script};
+
+    fn run_script(id: &str, script: Script, tasks: &[&str]) -> anyhow::Result<()> {
+        let tasks = tasks
+            .iter()
+            .map(|t| (t.to_string(), "{}".to_string()))
+            .collect();
+        let backend = FakeBackend { script };
+        run_with(
+            &backend,
+            id,
+            tasks,
+            Some(default_callback_log),
+            AdbSettings::default(),
+        )
+    }
+
+    #[test]
+    fn complete_tasks() {
+        run_script("fake-complete", Script::complete(&["Fight", "Mall"]), &[
+            "Fight", "Mall",
+        ])
+        .unwrap();
+        assert_eq!(state::get("fake-complete"), RunState::Idle);
+        assert!(!registry::is_running("fake-complete"));
+    }
+
+    #[test]
+    fn connect_failure() {
+        let script = Script {
+            connect_error: Some("device offline".to_string()),
+            ..Default::default()
+        };
+        assert!(run_script("fake-connect", script, &["Fight"]).is_err());
+        assert!(matches!(
+            state::get("fake-connect"),
+            RunState::Failed(e) if e.contains("device offline")
+        ));
+    }
+
+    #[test]
+    fn task_errors() {
+        let script = Script {
+            rejected_tasks: vec!["Mall".to_string()],
+            ..Script::complete(&["Fight"])
+        };
+        assert!(run_script("fake-append", script, &["Fight", "Mall"]).is_err());
+        assert!(matches!(state::get("fake-append"), RunState::Failed(_)));
+
+        // 任务链出错不影响后续任务，运行本身成功
+        let info = json!({ "taskchain": "Fight", "taskid": 1 });
+        let script = Script {
+            events: vec![
+                (AsstMsgCode::TaskChainStart, info.clone()),
+                (AsstMsgCode::TaskChainError, info),
+                (AsstMsgCode::AllTasksCompleted, json!({})),
+            ],
+            ..Default::default()
+        };
+        run_script("fake-chain-error", script, &["Fight"]).unwrap();
+        assert_eq!(state::get("fake-chain-error"), RunState::Idle);
+    }
+
+    #[test]
+    fn stop_running() {
+        let script = Script {
+            hold: true,
+            ..Script::complete(&[])
+        };
+        let script = Script {
+            events: vec![(
+                AsstMsgCode::TaskChainStart,
+                json!({ "taskchain": "Fight", "taskid": 1 }),
+            )],
+            ..script
+        };
+        let run = thread::spawn(move || run_script("fake-stop", script, &["Fight"]));
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while !matches!(state::get("fake-stop"), RunState::Running(Some(_))) {
+            assert!(
+                Instant::now() < deadline,
+                "fake-stop is still {:?}",
+                state::get("fake-stop")
+            );
+            thread::sleep(Duration::from_millis(5));
+        }
+        assert!(run_script("fake-stop", Script::default(), &["Fight"]).is_err());
+
+        registry::stop("fake-stop").unwrap();
+        run.join().unwrap().unwrap();
+        assert_eq!(state::get("fake-stop"), RunState::Idle);
+        assert!(registry::stop("fake-stop").is_err());
+    }
+}
@@ -1,8 +1,14 @@
 #![deny(warnings)]
 #![feature(once_cell_try)]
 
+pub mod backend;
 pub mod core;
+#[cfg(feature = "dynamic-log-level")]
+pub mod logger;
+pub mod registry;
+pub mod state;
 #[cfg(feature = "tauri-handle")]
 pub mod tauri_logger;
+pub mod tools;
 
 pub use core::*;
@@ -0,0 +1,94 @@
+//! gui 和命令行共用的日志配置，日志文件滚动保存在 `debug/` 下
+
+use anyhow::Context;
+use log::LevelFilter;
+use log4rs::{
+    Config,
+    append::{
+        console::ConsoleAppender,
+        rolling_file::{
+            RollingFileAppender,
+            policy::compound::{
+                CompoundPolicy, roll::fixed_window::FixedWindowRoller, trigger::size::SizeTrigger,
+            },
+        },
+    },
+    config::{Appender, Logger, Root},
+    encode::pattern::PatternEncoder,
+    filter::threshold::ThresholdFilter,
+};
+
+pub(crate) const ROLLING_FILE_APPENDER_NAME: &str = "file";
+const STDOUT_APPENDER_NAME: &str = "stdout";
+const LOG_FILE_PATH: &str = "debug/maa-se.log";
+const ACTIVE_CRATES_NAMES: &[&str] = &[
+    "maa_se_lib",
+    "maa_se_cli",
+    "maa_cfg",
+    "maa_core",
+    "maa_updater",
+    "maa_callback",
+];
+
+const MAX_LOG_SIZE: u64 = 10_000_000; // 10 MB
+const MAX_LOG_COUNT: u32 = 5;
+
+pub(crate) fn rolling_file_appender() -> Appender {
+    let trigger = SizeTrigger::new(MAX_LOG_SIZE);
+    let roller = FixedWindowRoller::builder()
+        .build(constcat::concat!(LOG_FILE_PATH, ".{}"), MAX_LOG_COUNT)
+        .unwrap();
+    let appender = RollingFileAppender::builder()
+        .build(
+            LOG_FILE_PATH,
+            Box::new(CompoundPolicy::new(Box::new(trigger), Box::new(roller))),
+        )
+        .unwrap();
+    Appender::builder().build(ROLLING_FILE_APPENDER_NAME, Box::new(appender))
+}
+
+/// 各 crate 的 logger，输出到给定的 appender
+pub(crate) fn crate_loggers(appenders: &[&str], level: LevelFilter) -> Vec<Logger> {
+    ACTIVE_CRATES_NAMES
+        .iter()
+        .map(|name| {
+            Logger::builder()
+                .appenders(appenders.iter().copied())
+                .additive(false)
+                .build(*name, level)
+        })
+        .collect()
+}
+
+/// 命令行使用的日志配置，
+/// stdout 在 INFO，
+/// file logger 在 `file_level`
+pub fn console_log_config(file_level: LevelFilter) -> anyhow::Result<Config> {
+    let stdout = Appender::builder()
+        .filter(Box::new(ThresholdFilter::new(LevelFilter::Info)))
+        .build(
+            STDOUT_APPENDER_NAME,
+            Box::new(
+                ConsoleAppender::builder()
+                    .encoder(Box::new(PatternEncoder::new("[{l}] {m}{n}")))
+                    .build(),
+            ),
+        );
+    let appenders = [ROLLING_FILE_APPENDER_NAME, STDOUT_APPENDER_NAME];
+    // 回调日志没有 gui 展示，同时写入文件
+    let callback_logger = Logger::builder()
+        .appenders(appenders)
+        .additive(false)
+        .build(maa_callback::msg_handler::GUI_TARGET, LevelFilter::Info);
+
+    let root = Root::builder()
+        .appender(ROLLING_FILE_APPENDER_NAME)
+        .build(LevelFilter::Error);
+
+    Config::builder()
+        .appenders([rolling_file_appender(), stdout])
+        .loggers(crate_loggers(&appenders, file_level))
+        .logger(callback_logger)
+        .build(root)
+        .context("build log4rs config")
+}
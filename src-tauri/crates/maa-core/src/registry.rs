@@ -0,0 +1,155 @@
+//! 正在运行的实例，按实例 id 区分，每个实例有独立的停止信号
+
+use std::{
+    ptr,
+    sync::{Arc, LazyLock},
+};
+
+use anyhow::{Context, bail};
+use chrono::{DateTime, Local};
+use dashmap::{DashMap, mapref::entry::Entry};
+use log::debug;
+use maa_callback::callback::RunContext;
+use maa_cfg::task::TaskQueue;
+use serde::Serialize;
+
+use crate::state::{self, RunState};
+
+pub type InstanceId = String;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RunInfo {
+    pub id: InstanceId,
+    /// 任务类型，按执行顺序
+    pub tasks: Vec<String>,
+    pub started_at: DateTime<Local>,
+}
+
+struct Run {
+    ctx: Arc<RunContext>,
+    info: RunInfo,
+}
+
+static RUNS: LazyLock<DashMap<InstanceId, Run>> = LazyLock::new(DashMap::new);
+
+/// 运行期间持有，drop 时从注册表移除
+pub(crate) struct RunGuard {
+    id: InstanceId,
+    pub ctx: Arc<RunContext>,
+}
+
+impl Drop for RunGuard {
+    fn drop(&mut self) {
+        RUNS.remove(&self.id);
+    }
+}
+
+/// 注册一次运行，同一实例不能同时运行多次
+pub(crate) fn register(id: &str, tasks: &TaskQueue) -> anyhow::Result<RunGuard> {
+    match RUNS.entry(id.to_string()) {
+        Entry::Occupied(_) => bail!("instance {id} is already running"),
+        Entry::Vacant(entry) => {
+            state::transit(id, RunState::LoadingResource)?;
+            let ctx = Arc::new(RunContext::new(id).with_task_hook(task_started));
+            entry.insert(Run {
+                ctx: ctx.clone(),
+                info: RunInfo {
+                    id: id.to_string(),
+                    tasks: tasks.iter().map(|(name, _)| name.clone()).collect(),
+                    started_at: Local::now(),
+                },
+            });
+            Ok(RunGuard {
+                id: id.to_string(),
+                ctx,
+            })
+        }
+    }
+}
+
+/// 回调在工作线程中处理，可能晚于本次运行结束，只处理仍在注册表中的运行
+fn task_started(ctx: &RunContext, task: &str) {
+    let Some(run) = RUNS.get(&ctx.id) else {
+        debug!("ignore task {task} of finished run {}", ctx.id);
+        return;
+    };
+    if !ptr::eq(Arc::as_ptr(&run.ctx), ctx) {
+        debug!("ignore task {task} of previous run {}", ctx.id);
+        return;
+    }
+    // 停止过程中仍可能收到任务开始的回调
+    if let Err(e) = state::transit(&ctx.id, RunState::Running(Some(task.into()))) {
+        debug!("{e}");
+    }
+}
+
+/// 请求停止指定实例
+pub fn stop(id: &str) -> anyhow::Result<()> {
+    let run = RUNS
+        .get(id)
+        .with_context(|| format!("instance {id} is not running"))?;
+    state::transit(id, RunState::Stopping)?;
+    run.ctx.stop();
+    Ok(())
+}
+
+/// 正在运行的实例数量
+pub fn count() -> usize {
+    RUNS.len()
+}
+
+pub fn is_running(id: &str) -> bool {
+    RUNS.contains_key(id)
+}
+
+pub fn status(id: &str) -> Option<RunInfo> {
+    RUNS.get(id).map(|r| r.info.clone())
+}
+
+/// 所有正在运行的实例，按开始时间排序
+pub fn list() -> Vec<RunInfo> {
+    let mut runs: Vec<_> = RUNS.iter().map(|r| r.info.clone()).collect();
+    runs.sort_by_key(|r| r.started_at);
+    runs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn register_and_stop() {
+        let tasks = vec![("Fight".to_string(), "{}".to_string())];
+        let guard = register("test-a", &tasks).unwrap();
+        assert!(register("test-a", &tasks).is_err());
+        assert!(is_running("test-a"));
+        assert_eq!(status("test-a").unwrap().tasks, ["Fight"]);
+
+        stop("test-a").unwrap();
+        stop("test-a").unwrap();
+        assert!(guard.ctx.stop_rx().try_recv().is_ok());
+        assert!(stop("test-b").is_err());
+
+        drop(guard);
+        assert!(!is_running("test-a"));
+    }
+
+    #[test]
+    fn ignore_stale_task_hook() {
+        let id = "test-stale";
+        let tasks = vec![("Fight".to_string(), "{}".to_string())];
+        let old = register(id, &tasks).unwrap();
+        let stale = old.ctx.clone();
+        stop(id).unwrap();
+        state::transit(id, RunState::Idle).unwrap();
+        drop(old);
+
+        let run = register(id, &tasks).unwrap();
+        task_started(&stale, "Fight");
+        assert_eq!(state::get(id), RunState::LoadingResource);
+
+        state::transit(id, RunState::Connecting).unwrap();
+        task_started(&run.ctx, "Fight");
+        assert_eq!(state::get(id), RunState::Running(Some("Fight".to_string())));
+    }
+}
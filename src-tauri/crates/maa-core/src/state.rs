@@ -0,0 +1,108 @@
+//! 每个实例的运行状态，状态变更时通知监听者
+
+use std::sync::{LazyLock, OnceLock};
+
+use anyhow::bail;
+use dashmap::DashMap;
+use log::debug;
+use serde::Serialize;
+
+use crate::registry::InstanceId;
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+#[serde(tag = "state", content = "detail")]
+pub enum RunState {
+    #[default]
+    Idle,
+    LoadingResource,
+    Connecting,
+    /// 正在执行的任务链，刚启动时为空
+    Running(Option<String>),
+    Stopping,
+    /// 失败原因
+    Failed(String),
+}
+
+impl RunState {
+    /// 是否正在运行，运行中的实例不能再次启动
+    pub fn is_active(&self) -> bool {
+        !matches!(self, RunState::Idle | RunState::Failed(_))
+    }
+
+    fn can_transit(&self, next: &RunState) -> bool {
+        use RunState::*;
+        match (self, next) {
+            (_, Failed(_)) => self.is_active(),
+            (Idle | Failed(_), LoadingResource) => true,
+            (LoadingResource, Connecting) => true,
+            (Connecting, Running(_)) => true,
+            (Running(_), Running(_)) => true,
+            (LoadingResource | Connecting | Running(_), Stopping) => true,
+            (Stopping, Idle) => true,
+            _ => false,
+        }
+    }
+}
+
+type Listener = Box<dyn Fn(&str, &RunState) + Send + Sync>;
+
+static STATES: LazyLock<DashMap<InstanceId, RunState>> = LazyLock::new(DashMap::new);
+static LISTENER: OnceLock<Listener> = OnceLock::new();
+
+/// 设置状态变更的监听者，只能设置一次
+pub fn set_listener(listener: impl Fn(&str, &RunState) + Send + Sync + 'static) {
+    if LISTENER.set(Box::new(listener)).is_err() {
+        log::warn!("run state listener is already set");
+    }
+}
+
+pub fn get(id: &str) -> RunState {
+    STATES.get(id).map(|s| s.clone()).unwrap_or_default()
+}
+
+/// 切换状态，不允许的切换会返回错误
+pub fn transit(id: &str, next: RunState) -> anyhow::Result<()> {
+    let mut state = STATES.entry(id.to_string()).or_default();
+    if *state == next {
+        return Ok(());
+    }
+    if !state.can_transit(&next) {
+        bail!("instance {id} can not switch from {:?} to {next:?}", *state);
+    }
+    debug!("instance {id}: {:?} -> {next:?}", *state);
+    *state = next.clone();
+    drop(state);
+
+    if let Some(listener) = LISTENER.get() {
+        listener(id, &next);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn transitions() {
+        let id = "state-test";
+        assert_eq!(get(id), RunState::Idle);
+        assert!(transit(id, RunState::Connecting).is_err());
+
+        transit(id, RunState::LoadingResource).unwrap();
+        assert!(transit(id, RunState::LoadingResource).is_ok());
+        assert!(get(id).is_active());
+        transit(id, RunState::Connecting).unwrap();
+        transit(id, RunState::Running(None)).unwrap();
+        transit(id, RunState::Running(Some("Fight".to_string()))).unwrap();
+        transit(id, RunState::Stopping).unwrap();
+        assert!(transit(id, RunState::Running(None)).is_err());
+        transit(id, RunState::Idle).unwrap();
+
+        assert!(transit(id, RunState::Failed("idle".to_string())).is_err());
+        transit(id, RunState::LoadingResource).unwrap();
+        transit(id, RunState::Failed("connect".to_string())).unwrap();
+        assert!(!get(id).is_active());
+        transit(id, RunState::LoadingResource).unwrap();
+    }
+}
@@ -1,8 +1,17 @@
 #[cfg(feature = "dynamic-log-level")]
 pub use dynamic_log::*;
+use serde::Serialize;
 use tauri::AppHandle;
 
-const CALLBACK_EVENT: &str = "callback-log";
+pub const CALLBACK_EVENT: &str = "callback-log";
+
+/// `callback-log` 事件的内容
+#[derive(Debug, Clone, Serialize)]
+pub struct CallbackLog {
+    /// 产生日志的实例 id，非任务日志为空
+    pub instance: Option<String>,
+    pub message: String,
+}
 
 #[derive(Debug)]
 pub struct SeAppender {
@@ -26,7 +35,11 @@ impl std::io::Write for SeAppender {
     fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
         let content = str::from_utf8(buf)
             .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e.to_string()))?;
-        match tauri::Emitter::emit(&self.app, CALLBACK_EVENT, content) {
+        let log = CallbackLog {
+            instance: None,
+            message: content.to_string(),
+        };
+        match tauri::Emitter::emit(&self.app, CALLBACK_EVENT, log) {
             Ok(_) => Ok(buf.len()),
             Err(e) => Err(std::io::Error::new(
                 std::io::ErrorKind::Other,
@@ -48,36 +61,16 @@ mod dynamic_log {
     use log::LevelFilter;
     use log4rs::{
         Config,
-        append::{
-            Append,
-            rolling_file::{
-                RollingFileAppender,
-                policy::compound::{
-                    CompoundPolicy, roll::fixed_window::FixedWindowRoller,
-                    trigger::size::SizeTrigger,
-                },
-            },
-        },
+        append::Append,
         config::{Appender, Logger, Root},
         encode,
     };
     use tauri::{AppHandle, Emitter};
 
-    use super::{CALLBACK_EVENT, SeAppender};
+    use super::{CALLBACK_EVENT, CallbackLog, SeAppender};
+    use crate::logger::{ROLLING_FILE_APPENDER_NAME, crate_loggers, rolling_file_appender};
 
-    const ROLLING_FILE_APPENDER_NAME: &str = "file";
     const CALLBACK_APPENDER_NAME: &str = "callback"; // TODO:换个更合适的名字
-    const LOG_FILE_PATH: &str = "debug/maa-se.log";
-    const ACTIVE_CRATES_NAMES: &[&str] = &[
-        "maa_se_lib",
-        "maa_cfg",
-        "maa_core",
-        "maa_updater",
-        "maa_callback",
-    ];
-
-    const MAX_LOG_SIZE: u64 = 10_000_000; // 10 MB
-    const MAX_LOG_COUNT: u32 = 5;
 
     struct StringWriter(String);
 
@@ -100,8 +93,13 @@ mod dynamic_log {
         fn append(&self, record: &log::Record) -> anyhow::Result<()> {
             let mut content = StringWriter(String::new());
             self.encoder.encode(&mut content, record)?;
+            let log = CallbackLog {
+                instance: maa_callback::msg_handler::instance_of(record.target())
+                    .map(str::to_string),
+                message: content.0,
+            };
             self.app
-                .emit(CALLBACK_EVENT, content.0)
+                .emit(CALLBACK_EVENT, log)
                 .map_err(|e| anyhow::anyhow!(e))
         }
 
@@ -113,32 +111,15 @@ mod dynamic_log {
     /// gui logger in INFO,
     /// file logger in `level`
     pub fn log_config(handle: AppHandle, file_level: LevelFilter) -> anyhow::Result<Config> {
-        // rolling file appender
-        let trigger = SizeTrigger::new(MAX_LOG_SIZE);
-        let roller = FixedWindowRoller::builder()
-            .build(constcat::concat!(LOG_FILE_PATH, ".{}"), MAX_LOG_COUNT)
-            .unwrap();
-        let appender = RollingFileAppender::builder()
-            .build(
-                LOG_FILE_PATH,
-                Box::new(CompoundPolicy::new(Box::new(trigger), Box::new(roller))),
-            )
-            .unwrap();
-        let rolling = Appender::builder().build(ROLLING_FILE_APPENDER_NAME, Box::new(appender));
-
-        let loggers = ACTIVE_CRATES_NAMES.iter().map(|name| {
-            Logger::builder()
-                .appender(ROLLING_FILE_APPENDER_NAME)
-                .additive(false)
-                .build(*name, file_level)
-        });
+        let rolling = rolling_file_appender();
+        let loggers = crate_loggers(&[ROLLING_FILE_APPENDER_NAME], file_level);
 
         let gui =
             Appender::builder().build(CALLBACK_APPENDER_NAME, Box::new(SeAppender::new(handle)));
         let gui_logger = Logger::builder()
             .appender(CALLBACK_APPENDER_NAME)
             .additive(false)
-            .build("maa_callback::msg_handler", LevelFilter::Info);
+            .build(maa_callback::msg_handler::GUI_TARGET, LevelFilter::Info);
 
         let root = Root::builder()
             .appender(ROLLING_FILE_APPENDER_NAME)
@@ -0,0 +1,83 @@
+//! 保存小工具的识别结果和作战掉落，并导出为其他工具使用的格式
+
+pub mod depot;
+pub mod oper_box;
+mod recruit;
+pub mod recruit_calc;
+
+use anyhow::Context;
+use chrono::Local;
+use maa_callback::{
+    callback_types::StageDrops,
+    event::{ExtraInfo, MaaEvent},
+};
+use maa_cfg::{
+    Config, ConfigValue,
+    ledger::{RECRUIT_LEDGER, RecruitRecord, STAGE_DROPS_LEDGER, StageDropRecord},
+};
+
+use self::{depot::DEPOT_TOOL, oper_box::OPER_BOX_TOOL};
+
+/// 需要保存的识别结果
+pub enum ToolResult {
+    /// 保存到 `tool-storage.json`
+    Tool {
+        tool: &'static str,
+        data: ConfigValue,
+    },
+    /// 追加到掉落记录
+    StageDrops(StageDropRecord),
+    /// 追加到公招记录
+    Recruit(RecruitRecord),
+}
+
+impl ToolResult {
+    /// 只有识别完成的结果需要保存，公招在一次招募结束时保存
+    ///
+    /// 需要按顺序传入实例的每个事件
+    pub fn from_event(instance: &str, event: &MaaEvent) -> anyhow::Result<Option<Self>> {
+        if let Some(record) = recruit::track(instance, event) {
+            return Ok(Some(Self::Recruit(record)));
+        }
+        let MaaEvent::SubTaskExtraInfo(extra) = event else {
+            return Ok(None);
+        };
+        let result = match &extra.info {
+            ExtraInfo::DepotInfo(depot) if depot.done => Self::Tool {
+                tool: DEPOT_TOOL,
+                data: serde_json::to_value(depot).context("serde depot")?,
+            },
+            ExtraInfo::OperBoxInfo(oper_box) if oper_box.done => Self::Tool {
+                tool: OPER_BOX_TOOL,
+                data: serde_json::to_value(oper_box).context("serde oper box")?,
+            },
+            ExtraInfo::StageDrops(drops) => Self::StageDrops(stage_drop_record(drops)),
+            _ => return Ok(None),
+        };
+        Ok(Some(result))
+    }
+
+    pub async fn save(self, config: &Config) -> anyhow::Result<()> {
+        match self {
+            Self::Tool { tool, data } => config.record_tool(tool, data).await,
+            Self::StageDrops(record) => config.append_ledger(STAGE_DROPS_LEDGER, &record).await,
+            Self::Recruit(record) => config.append_ledger(RECRUIT_LEDGER, &record).await,
+        }
+    }
+}
+
+fn stage_drop_record(drops: &StageDrops) -> StageDropRecord {
+    StageDropRecord {
+        time: Local::now(),
+        stage_code: drops.stage.stage_code.clone(),
+        stage_id: drops.stage.stage_id.clone(),
+        stars: drops.stars,
+        ap_cost: drops.stage.ap_cost,
+        drops: drops
+            .stats
+            .iter()
+            .filter(|stat| stat.add_quantity > 0)
+            .map(|stat| (stat.item_name.clone(), stat.add_quantity))
+            .collect(),
+    }
+}
@@ -0,0 +1,121 @@
+use std::fmt::Write;
+
+use anyhow::Context;
+use chrono::{DateTime, Local};
+use maa_callback::callback_types::Depot;
+use maa_cfg::{Config, csv_field};
+use serde::Serialize;
+use strum::{Display, EnumString};
+
+/// `tool-storage.json` 中仓库识别结果的键
+pub const DEPOT_TOOL: &str = "Depot";
+
+#[derive(Debug, Serialize)]
+pub struct DepotRecord {
+    pub time: DateTime<Local>,
+    pub depot: Depot,
+}
+
+/// 最近一次仓库识别的结果
+pub fn latest_depot(config: &Config) -> anyhow::Result<Option<DepotRecord>> {
+    depot_history(config).map(|mut history| history.pop())
+}
+
+/// 全部仓库识别结果，从旧到新
+pub fn depot_history(config: &Config) -> anyhow::Result<Vec<DepotRecord>> {
+    config
+        .tool_history(DEPOT_TOOL)?
+        .into_iter()
+        .map(|record| {
+            Ok(DepotRecord {
+                time: record.time,
+                depot: serde_json::from_value(record.data).context("parse depot record")?,
+            })
+        })
+        .collect()
+}
+
+#[derive(Debug, Clone, Copy, Display, EnumString)]
+#[strum(serialize_all = "lowercase")]
+pub enum DepotFormat {
+    /// 企鹅物流刷图规划器
+    ArkPlanner,
+    /// 明日方舟工具箱（lolicon）
+    Lolicon,
+    /// 物品 id、名称和数量
+    Csv,
+}
+
+impl DepotFormat {
+    pub fn export(self, depot: &Depot) -> anyhow::Result<String> {
+        match self {
+            DepotFormat::ArkPlanner => Ok(depot.arkplanner.data.clone()),
+            DepotFormat::Lolicon => Ok(depot.lolicon.data.clone()),
+            DepotFormat::Csv => {
+                let mut s = String::from("id,name,count\n");
+                for item in &depot.arkplanner.object.items {
+                    writeln!(s, "{},{},{}", item.id, csv_field(&item.name), item.have)?;
+                }
+                Ok(s)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use maa_callback::{callback::AsstMsgCode, event::MaaEvent};
+    use serde_json::json;
+
+    use super::*;
+    use crate::tools::ToolResult;
+
+    fn depot_event(done: bool) -> MaaEvent {
+        let msg = json!({
+            "taskchain": "Depot",
+            "what": "DepotInfo",
+            "details": {
+                "done": done,
+                "arkplanner": {
+                    "object": {
+                        "items": [
+                            { "id": "2004", "have": 4, "name": "高级作战记录" },
+                            { "id": "x", "have": 1, "name": "a,\"b\"" }
+                        ],
+                        "@type": "@penguin-statistics/depot"
+                    },
+                    "data": "{\"items\":[]}"
+                },
+                "lolicon": { "object": { "2004": 4, "x": 1 }, "data": "{\"2004\":4}" }
+            }
+        });
+        MaaEvent::parse(AsstMsgCode::SubTaskExtraInfo, &msg.to_string()).unwrap()
+    }
+
+    #[test]
+    fn export_depot() {
+        assert!(
+            ToolResult::from_event("depot-test", &depot_event(false))
+                .unwrap()
+                .is_none()
+        );
+        let Some(ToolResult::Tool { tool, data }) =
+            ToolResult::from_event("depot-test", &depot_event(true)).unwrap()
+        else {
+            panic!("depot is not saved");
+        };
+        assert_eq!(tool, DEPOT_TOOL);
+
+        let depot: Depot = serde_json::from_value(data).unwrap();
+        assert_eq!(
+            DepotFormat::Csv.export(&depot).unwrap(),
+            "id,name,count\n2004,高级作战记录,4\nx,\"a,\"\"b\"\"\",1\n"
+        );
+        assert_eq!(
+            DepotFormat::ArkPlanner.export(&depot).unwrap(),
+            "{\"items\":[]}"
+        );
+        assert_eq!(DepotFormat::Lolicon.export(&depot).unwrap(), "{\"2004\":4}");
+        assert!(matches!("arkplanner".parse(), Ok(DepotFormat::ArkPlanner)));
+    }
+}
@@ -0,0 +1,198 @@
+use std::{collections::HashMap, fmt::Write};
+
+use anyhow::Context;
+use chrono::{DateTime, Local};
+use maa_callback::callback_types::{OperBox, OwnOperInfo};
+use maa_cfg::{Config, csv_field};
+use serde::Serialize;
+use strum::{Display, EnumString};
+
+/// `tool-storage.json` 中干员识别结果的键
+pub const OPER_BOX_TOOL: &str = "OperBox";
+
+#[derive(Debug, Serialize)]
+pub struct OperBoxRecord {
+    pub time: DateTime<Local>,
+    pub oper_box: OperBox,
+}
+
+/// 全部干员识别结果，从旧到新
+pub fn oper_box_history(config: &Config) -> anyhow::Result<Vec<OperBoxRecord>> {
+    config
+        .tool_history(OPER_BOX_TOOL)?
+        .into_iter()
+        .map(|record| {
+            Ok(OperBoxRecord {
+                time: record.time,
+                oper_box: serde_json::from_value(record.data).context("parse oper box record")?,
+            })
+        })
+        .collect()
+}
+
+#[derive(Debug, PartialEq, Eq, Serialize)]
+pub struct OperChange {
+    pub id: String,
+    pub name: String,
+    pub from: u8,
+    pub to: u8,
+}
+
+/// 两次干员识别之间的变化
+#[derive(Debug, Default, Serialize)]
+pub struct OperBoxDiff {
+    pub from: Option<DateTime<Local>>,
+    pub to: Option<DateTime<Local>>,
+    /// 新获得的干员
+    pub new_opers: Vec<OwnOperInfo>,
+    /// 精英化阶段提升
+    pub promotions: Vec<OperChange>,
+    /// 潜能提升
+    pub potentials: Vec<OperChange>,
+}
+
+impl OperBoxDiff {
+    pub fn new(from: &OperBoxRecord, to: &OperBoxRecord) -> Self {
+        let old: HashMap<_, _> = from
+            .oper_box
+            .own_opers
+            .iter()
+            .map(|oper| (oper.id.as_str(), oper))
+            .collect();
+
+        let mut diff = Self {
+            from: Some(from.time),
+            to: Some(to.time),
+            ..Default::default()
+        };
+        for oper in &to.oper_box.own_opers {
+            let Some(prev) = old.get(oper.id.as_str()) else {
+                diff.new_opers.push(oper.clone());
+                continue;
+            };
+            let change = |from, to| OperChange {
+                id: oper.id.clone(),
+                name: oper.name.clone(),
+                from,
+                to,
+            };
+            if oper.elite != prev.elite {
+                diff.promotions.push(change(prev.elite, oper.elite));
+            }
+            if oper.potential != prev.potential {
+                diff.potentials.push(change(prev.potential, oper.potential));
+            }
+        }
+        diff
+    }
+}
+
+/// 比较历史中的两次识别结果，下标从旧到新，默认比较最近的两次
+pub fn diff_oper_box(
+    config: &Config,
+    from: Option<usize>,
+    to: Option<usize>,
+) -> anyhow::Result<OperBoxDiff> {
+    let history = oper_box_history(config)?;
+    let to = match to {
+        Some(to) => to,
+        None => history
+            .len()
+            .checked_sub(1)
+            .context("no oper box recognition result, run OperBox first")?,
+    };
+    let from = from.unwrap_or(to.saturating_sub(1));
+    let record = |i: usize| {
+        history
+            .get(i)
+            .with_context(|| format!("no oper box record at {i}"))
+    };
+    Ok(OperBoxDiff::new(record(from)?, record(to)?))
+}
+
+#[derive(Debug, Clone, Copy, Display, EnumString)]
+#[strum(serialize_all = "lowercase")]
+pub enum OperBoxFormat {
+    /// 已拥有干员的 json 数组
+    Json,
+    /// 已拥有干员的 id、名称、星级、精英化、等级和潜能
+    Csv,
+}
+
+impl OperBoxFormat {
+    pub fn export(self, oper_box: &OperBox) -> anyhow::Result<String> {
+        match self {
+            OperBoxFormat::Json => {
+                serde_json::to_string_pretty(&oper_box.own_opers).context("serde own opers")
+            }
+            OperBoxFormat::Csv => {
+                let mut s = String::from("id,name,rarity,elite,level,potential\n");
+                for oper in &oper_box.own_opers {
+                    writeln!(
+                        s,
+                        "{},{},{},{},{},{}",
+                        oper.id,
+                        csv_field(&oper.name),
+                        oper.rarity,
+                        oper.elite,
+                        oper.level,
+                        oper.potential
+                    )?;
+                }
+                Ok(s)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn record(own_opers: serde_json::Value) -> OperBoxRecord {
+        let oper_box = json!({ "done": true, "all_oper": [], "own_opers": own_opers });
+        OperBoxRecord {
+            time: Local::now(),
+            oper_box: serde_json::from_value(oper_box).unwrap(),
+        }
+    }
+
+    #[test]
+    fn diff_snapshots() {
+        let amiya = |elite, potential| {
+            json!({
+                "id": "char_002_amiya", "name": "阿米娅", "own": true,
+                "elite": elite, "level": 1, "potential": potential, "rarity": 5
+            })
+        };
+        let from = record(json!([amiya(1, 1)]));
+        let to = record(json!([
+            amiya(2, 3),
+            {
+                "id": "char_010_chen", "name": "陈", "own": true,
+                "elite": 0, "level": 1, "potential": 1, "rarity": 6
+            }
+        ]));
+
+        let diff = OperBoxDiff::new(&from, &to);
+        assert_eq!(diff.new_opers.len(), 1);
+        assert_eq!(diff.new_opers[0].name, "陈");
+        let change = |from, to| OperChange {
+            id: "char_002_amiya".to_string(),
+            name: "阿米娅".to_string(),
+            from,
+            to,
+        };
+        assert_eq!(diff.promotions, [change(1, 2)]);
+        assert_eq!(diff.potentials, [change(1, 3)]);
+
+        assert_eq!(
+            OperBoxFormat::Csv.export(&to.oper_box).unwrap(),
+            "id,name,rarity,elite,level,potential\n\
+             char_002_amiya,阿米娅,5,2,1,3\n\
+             char_010_chen,陈,6,0,1,1\n"
+        );
+    }
+}
@@ -0,0 +1,132 @@
+use std::sync::LazyLock;
+
+use chrono::Local;
+use dashmap::DashMap;
+use maa_callback::event::{ExtraInfo, MaaEvent};
+use maa_cfg::ledger::RecruitRecord;
+
+use crate::registry::InstanceId;
+
+/// 公招确认招募的步骤，会消耗一张招聘许可
+const RECRUIT_CONFIRM: &str = "RecruitConfirm";
+
+/// 正在进行的招募，一个栏位可能经过多次刷新
+#[derive(Default)]
+struct Attempt {
+    record: RecruitRecord,
+    /// 刷新后的下一次识别仍属于同一次招募
+    refreshing: bool,
+}
+
+static ATTEMPTS: LazyLock<DashMap<InstanceId, Attempt>> = LazyLock::new(DashMap::new);
+
+/// 按实例跟踪公招过程，一次招募结束时返回其记录
+pub(crate) fn track(instance: &str, event: &MaaEvent) -> Option<RecruitRecord> {
+    let extra = match event {
+        MaaEvent::SubTaskExtraInfo(extra) => &extra.info,
+        MaaEvent::SubTaskStart(task) => {
+            if task.details["task"] == RECRUIT_CONFIRM
+                && let Some(mut attempt) = ATTEMPTS.get_mut(instance)
+            {
+                attempt.record.permit_used = true;
+            }
+            return None;
+        }
+        MaaEvent::TaskChainCompleted(_)
+        | MaaEvent::TaskChainStopped(_)
+        | MaaEvent::TaskChainError(_)
+        | MaaEvent::AllTasksCompleted(_) => return finish(instance),
+        _ => return None,
+    };
+
+    match extra {
+        ExtraInfo::RecruitTagsDetected(detected) => {
+            let refreshing = ATTEMPTS.get(instance).is_some_and(|a| a.refreshing);
+            let finished = if refreshing { None } else { finish(instance) };
+            let mut attempt = ATTEMPTS.entry(instance.to_string()).or_default();
+            attempt.refreshing = false;
+            attempt.record.time = Local::now();
+            attempt.record.tags = detected.tags.clone();
+            return finished;
+        }
+        ExtraInfo::RecruitSlotCompleted => return finish(instance),
+        _ => {}
+    }
+
+    let mut attempt = ATTEMPTS.get_mut(instance)?;
+    let record = &mut attempt.record;
+    match extra {
+        ExtraInfo::RecruitSpecialTag(special) => record.special_tags.push(special.tag.clone()),
+        ExtraInfo::RecruitResult(result) => record.level = result.level,
+        ExtraInfo::RecruitTagsSelected(selected) => record.selected_tags = selected.tags.clone(),
+        ExtraInfo::RecruitNoPermit(_) => record.no_permit = true,
+        ExtraInfo::RecruitTagsRefreshed(_) => {
+            record.refreshes += 1;
+            record.special_tags.clear();
+            attempt.refreshing = true;
+        }
+        _ => {}
+    }
+    None
+}
+
+fn finish(instance: &str) -> Option<RecruitRecord> {
+    ATTEMPTS.remove(instance).map(|(_, attempt)| attempt.record)
+}
+
+#[cfg(test)]
+mod tests {
+    use maa_callback::callback::AsstMsgCode;
+    use serde_json::json;
+
+    use super::*;
+
+    fn extra(what: &str, details: serde_json::Value) -> MaaEvent {
+        let msg = json!({ "taskchain": "Recruit", "what": what, "details": details });
+        MaaEvent::parse(AsstMsgCode::SubTaskExtraInfo, &msg.to_string()).unwrap()
+    }
+
+    fn result(level: u8) -> MaaEvent {
+        extra(
+            "RecruitResult",
+            json!({ "tags": [], "level": level, "result": [] }),
+        )
+    }
+
+    #[test]
+    fn track_attempts() {
+        let id = "recruit-test";
+        let confirm = json!({ "subtask": "ProcessTask", "details": { "task": "RecruitConfirm" } });
+        let confirm = MaaEvent::parse(AsstMsgCode::SubTaskStart, &confirm.to_string()).unwrap();
+
+        let events = [
+            extra("RecruitTagsDetected", json!({ "tags": ["a"] })),
+            result(3),
+            extra(
+                "RecruitTagsRefreshed",
+                json!({ "count": 1, "refresh_limit": 3 }),
+            ),
+            extra("RecruitTagsDetected", json!({ "tags": ["b"] })),
+            extra("RecruitSpecialTag", json!({ "tag": "高级资深干员" })),
+            result(6),
+            extra("RecruitTagsSelected", json!({ "tags": ["b"] })),
+            confirm,
+        ];
+        assert!(events.iter().all(|e| track(id, e).is_none()));
+        let record = track(id, &extra("RecruitSlotCompleted", json!({}))).unwrap();
+        assert_eq!(record.tags, ["b"]);
+        assert_eq!(record.special_tags, ["高级资深干员"]);
+        assert_eq!(record.level, 6);
+        assert_eq!(record.refreshes, 1);
+        assert!(record.permit_used);
+
+        // 未招募的栏位在下一次识别时结束
+        track(id, &extra("RecruitTagsDetected", json!({ "tags": ["c"] })));
+        track(id, &result(4));
+        track(id, &extra("RecruitNoPermit", json!({ "continue": false })));
+        let record = track(id, &extra("RecruitTagsDetected", json!({ "tags": ["d"] }))).unwrap();
+        assert_eq!(record.tags, ["c"]);
+        assert!(record.no_permit && !record.permit_used);
+        assert_eq!(finish(id).unwrap().tags, ["d"]);
+    }
+}
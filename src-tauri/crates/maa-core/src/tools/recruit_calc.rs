@@ -0,0 +1,142 @@
+//! 离线公招计算，数据来自 MaaCore 的 `resource/recruitment.json`
+
+use std::{env::current_dir, fs, path::Path};
+
+use anyhow::{Context, ensure};
+use itertools::Itertools;
+use maa_callback::callback_types::{Oper, ResultEntry};
+use serde::Deserialize;
+
+pub const RECRUITMENT_JSON: &str = "resource/recruitment.json";
+/// 公招最多同时显示的标签数
+pub const MAX_TAGS: usize = 5;
+/// 一次最多选择的标签数
+const MAX_SELECTED: usize = 3;
+/// 只有选择该标签时才可能招募到六星干员
+const TOP_OPERATOR: &str = "高级资深干员";
+const SENIOR_OPERATOR: &str = "资深干员";
+/// 招募时间足够长时不会出现的低星干员
+const MIN_GUARANTEED_LEVEL: u8 = 3;
+
+#[derive(Debug, Deserialize)]
+pub struct Recruitment {
+    operators: Vec<RecruitOper>,
+}
+
+#[derive(Debug, Deserialize)]
+struct RecruitOper {
+    name: String,
+    rarity: u8,
+    #[serde(default)]
+    tags: Vec<String>,
+}
+
+impl RecruitOper {
+    fn has_tag(&self, tag: &str) -> bool {
+        match tag {
+            TOP_OPERATOR => self.rarity == 6,
+            SENIOR_OPERATOR => self.rarity == 5,
+            _ => self.tags.iter().any(|t| t == tag),
+        }
+    }
+}
+
+impl Recruitment {
+    /// 读取当前目录下的资源
+    pub fn load() -> anyhow::Result<Self> {
+        Self::load_from(&current_dir().context("cwd")?.join(RECRUITMENT_JSON))
+    }
+
+    pub fn load_from(path: &Path) -> anyhow::Result<Self> {
+        let content = fs::read_to_string(path)
+            .with_context(|| format!("read {path:?}, run `update resource` first"))?;
+        serde_json::from_str(&content).with_context(|| format!("parse {path:?}"))
+    }
+
+    /// 所有可能招募到干员的标签组合，按保底星级从高到低排列
+    pub fn calculate(&self, tags: &[String]) -> anyhow::Result<Vec<ResultEntry>> {
+        ensure!(
+            !tags.is_empty() && tags.len() <= MAX_TAGS,
+            "expected 1 to {MAX_TAGS} tags, got {}",
+            tags.len()
+        );
+        let tags = tags.iter().unique().collect_vec();
+        let mut results = (1..=MAX_SELECTED.min(tags.len()))
+            .flat_map(|n| tags.iter().copied().combinations(n))
+            .filter_map(|combination| self.entry(combination))
+            .collect_vec();
+        results.sort_by(|a, b| {
+            b.level
+                .cmp(&a.level)
+                .then(a.opers.len().cmp(&b.opers.len()))
+        });
+        Ok(results)
+    }
+
+    fn entry(&self, tags: Vec<&String>) -> Option<ResultEntry> {
+        let top = tags.iter().any(|t| *t == TOP_OPERATOR);
+        let opers = self
+            .operators
+            .iter()
+            .filter(|oper| top || oper.rarity < 6)
+            .filter(|oper| tags.iter().all(|t| oper.has_tag(t)))
+            .sorted_by(|a, b| b.rarity.cmp(&a.rarity))
+            .collect_vec();
+        // 低星干员只在它们是唯一结果时计入保底
+        let level = opers
+            .iter()
+            .map(|oper| oper.rarity)
+            .filter(|rarity| *rarity >= MIN_GUARANTEED_LEVEL)
+            .min()
+            .or_else(|| opers.iter().map(|oper| oper.rarity).min())?;
+        Some(ResultEntry {
+            tags: tags.into_iter().cloned().collect(),
+            level,
+            opers: opers
+                .into_iter()
+                .map(|oper| Oper {
+                    name: oper.name.clone(),
+                    level: oper.rarity.into(),
+                })
+                .collect(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn calculate_combinations() {
+        let recruitment: Recruitment = serde_json::from_value(json!({
+            "operators": [
+                { "id": "a", "name": "能天使", "rarity": 6, "tags": ["狙击干员", "远程位", "输出"] },
+                { "id": "b", "name": "白雪", "rarity": 4, "tags": ["狙击干员", "远程位", "群攻", "减速"] },
+                { "id": "c", "name": "杰西卡", "rarity": 4, "tags": ["狙击干员", "远程位", "输出", "生存"] },
+                { "id": "d", "name": "克洛丝", "rarity": 3, "tags": ["狙击干员", "远程位", "输出"] },
+                { "id": "e", "name": "Castle-3", "rarity": 1, "tags": ["近卫干员", "近战位", "支援机械"] }
+            ]
+        }))
+        .unwrap();
+
+        let tags = ["狙击干员", "生存", "高级资深干员", "支援机械"].map(String::from);
+        let results = recruitment.calculate(&tags).unwrap();
+        assert_eq!(results[0].level, 6);
+        assert_eq!(results[0].tags, ["高级资深干员"]);
+        assert_eq!(results[0].opers[0].name, "能天使");
+
+        let survive = results.iter().find(|r| r.tags == ["生存"]).unwrap();
+        assert_eq!(survive.level, 4);
+        let sniper = results.iter().find(|r| r.tags == ["狙击干员"]).unwrap();
+        assert_eq!(sniper.level, 3);
+        assert_eq!(sniper.opers.len(), 3);
+        let robot = results.iter().find(|r| r.tags == ["支援机械"]).unwrap();
+        assert_eq!(robot.level, 1);
+        assert!(!results.iter().any(|r| r.tags == ["生存", "支援机械"]));
+
+        assert!(recruitment.calculate(&[]).is_err());
+    }
+}
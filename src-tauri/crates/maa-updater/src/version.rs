@@ -4,13 +4,15 @@ use anyhow::{Context, bail};
 use chrono::NaiveDateTime;
 use semver::Version;
 use serde::{Deserialize, Serialize};
+use strum::EnumString;
 
 pub const CLIENT_VERSION_JSON: &str = "client_version.json";
 pub const RESOURCE_VERSION_JSON: &str = "version.json";
 pub const RESOURCE_TIMESTAMP_FORMAT: &str = "%Y-%m-%d %H:%M:%S%.3f";
 
-#[derive(Deserialize)]
+#[derive(Debug, Clone, Copy, Deserialize, EnumString)]
 #[serde(rename_all(deserialize = "lowercase"))]
+#[strum(serialize_all = "lowercase")]
 pub enum ClientVersionRequest {
     Nightly,
     Beta,
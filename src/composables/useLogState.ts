@@ -5,15 +5,22 @@ import { ref } from 'vue'
 interface Log {
   message: string
   type: 'info' | 'warning' | 'error'
+  instance?: string
+}
+
+interface CallbackLog {
+  instance: string | null
+  message: string
 }
 
 const logs = ref<Log[]>([])
 
 async function setupLogListener() {
-  await listen('callback-log', (event: { payload: string }) => {
+  await listen('callback-log', (event: { payload: CallbackLog }) => {
     logs.value.push({
-      message: event.payload,
+      message: event.payload.message,
       type: 'info',
+      instance: event.payload.instance ?? undefined,
     })
   })
 }